@@ -0,0 +1,184 @@
+## Dates
+
+date-today = Today
+date-yesterday = Yesterday
+# Day of the current month, e.g. "21st"
+date-this-month = { $day }{ ORDINAL($day) ->
+    [one] st
+    [two] nd
+    [few] rd
+   *[other] th
+}
+date-full = { $day } of { $month }, { $year }
+
+month-1 = January
+month-2 = February
+month-3 = March
+month-4 = April
+month-5 = May
+month-6 = June
+month-7 = July
+month-8 = August
+month-9 = September
+month-10 = October
+month-11 = November
+month-12 = December
+
+weekday-mon = Monday
+weekday-tue = Tuesday
+weekday-wed = Wednesday
+weekday-thu = Thursday
+weekday-fri = Friday
+weekday-sat = Saturday
+weekday-sun = Sunday
+
+weekday-short-mon = M
+weekday-short-tue = T
+weekday-short-wed = W
+weekday-short-thu = T
+weekday-short-fri = F
+weekday-short-sat = S
+weekday-short-sun = S
+
+## Navigation
+
+nav-days = Days
+nav-week = Week
+nav-month = Month
+nav-search = Search
+nav-stats = Stats
+nav-settings = Settings
+profile-default = Default profile
+
+window-minimize = Minimize
+window-maximize = Maximize
+window-close = Close
+
+## Days
+
+app-title = Ritual
+add-day = Add Day
+save = Save
+new-habit-placeholder = New Habit
+habit-color-reset = Reset
+habit-note-placeholder = Note
+
+conflict-message = The database was changed by another program while you had unsaved changes
+conflict-keep-local = Keep mine
+conflict-use-external = Use theirs
+
+mood = Mood
+journal-add = Write in journal
+journal-edit = Edit journal
+journal-placeholder = How did today go? Markdown works here
+
+habit-not-found = This habit does not exist
+habit-archived = Archived, it is no longer added to new days
+habit-archive = Archive
+habit-unarchive = Unarchive
+habit-notes = Notes
+habit-strength = Strength
+
+grid-previous = Previous
+grid-next = Next
+
+search-title = Search
+search-placeholder = Habits, notes and journals
+search-hint = Filter with from:2025-01-01, to:2025-01-31, is:done or is:not-done, and put "exact phrases" in quotes.
+search-no-results = Nothing found
+search-journal = Journal
+
+stats-title = Stats
+stats-habit = Habit
+stats-done = Done
+stats-completion = Completion
+stats-current-streak = Streak
+stats-longest-streak = Longest streak
+stats-trends-title = Trends
+stats-trend-completion = Completion by week
+stats-trend-per-week = Habits done per week
+stats-trend-per-weekday = Habits done by day of the week
+stats-mood-title = Mood
+stats-mood-rated = { $days ->
+    [one] One day rated.
+   *[other] { $days } days rated.
+}
+stats-mood-positive = Better days tend to be the ones you do more of your habits.
+stats-mood-negative = Better days tend to be the ones you do fewer of your habits.
+stats-mood-none = Mood does not seem to follow how many habits you do.
+stats-mood-unknown = Rate a few more days to see how mood relates to your habits.
+stats-mood-when-done = Mood when done
+stats-mood-when-not-done = Mood otherwise
+
+pause = Pause
+pause-all-habits = All habits
+pause-until = Last paused day
+pause-all = All habits are paused, last day: { $end }
+pause-habit = { $habit } is paused, last day: { $end }
+pause-resume = Resume
+
+unlock-title = Unlock
+unlock-placeholder = Passphrase
+unlock = Unlock
+unlock-failed = Wrong passphrase
+
+reminder-body = Scheduled for { $time }, not done yet
+
+## Settings
+
+settings-title = Settings
+settings-db-path = Database path of the default profile
+settings-timezone = Timezone
+settings-timezone-placeholder = System timezone
+settings-day-rollover-hour = Day rollover hour
+settings-week-start = Week starts on
+settings-theme = Theme
+settings-locale = Language
+settings-autosave-interval = Autosave interval (seconds, 0 to disable)
+settings-shortcuts = Shortcuts
+settings-shortcut-new-day = New day
+settings-shortcut-save = Save
+settings-shortcut-home = Go to days
+settings-shortcut-settings = Go to settings
+settings-api = HTTP API
+settings-api-enabled = Enable the local API
+settings-api-port = Port
+settings-api-token = Access token
+settings-api-token-generate = Generate
+settings-history = Keep a git history of the database
+settings-sync = Sync
+settings-sync-server = Sync server
+settings-sync-interval = Sync interval (seconds, 0 to only sync on demand)
+settings-daily-notes = Daily notes
+settings-daily-notes-folder = Folder of the Markdown daily notes
+settings-daily-notes-template = Template of the block written into each note
+settings-save = Save settings
+settings-saved = Saved
+settings-saved-restart = Saved, restart to apply the changes
+settings-save-failed = Failed to save: { $error }
+
+theme-light = Light
+theme-dark = Dark
+theme-system = System
+
+status-pending = Pending
+status-done = Done
+status-skipped = Skipped
+status-failed = Failed
+locale-system = System
+
+error-db-path-empty = Database path must not be empty
+error-db-path-dir = Database path is a directory
+error-rollover-hour = Must be an hour between 0 and 23
+error-shortcut-taken = { $shortcut } is already bound to another action
+error-shortcut-invalid = { $shortcut } is not a valid shortcut
+error-timezone = Unknown timezone { $timezone }
+error-number = Must be a whole number
+error-weekday = Unknown weekday
+error-theme = Unknown theme
+error-locale = Unknown language
+error-port = Must be a port between 1 and 65535
+error-api-token = A token is required to enable the API
+error-profile-unknown = Unknown profile { $profile }
+error-db-failed = Failed to open the database: { $error }
+error-daily-notes-folder = Daily notes folder is a file
@@ -0,0 +1,179 @@
+## Dates
+
+date-today = Hoy
+date-yesterday = Ayer
+# Day of the current month, e.g. "día 21"
+date-this-month = día { $day }
+date-full = { $day } de { $month } de { $year }
+
+month-1 = enero
+month-2 = febrero
+month-3 = marzo
+month-4 = abril
+month-5 = mayo
+month-6 = junio
+month-7 = julio
+month-8 = agosto
+month-9 = septiembre
+month-10 = octubre
+month-11 = noviembre
+month-12 = diciembre
+
+weekday-mon = Lunes
+weekday-tue = Martes
+weekday-wed = Miércoles
+weekday-thu = Jueves
+weekday-fri = Viernes
+weekday-sat = Sábado
+weekday-sun = Domingo
+
+weekday-short-mon = L
+weekday-short-tue = M
+weekday-short-wed = X
+weekday-short-thu = J
+weekday-short-fri = V
+weekday-short-sat = S
+weekday-short-sun = D
+
+## Navigation
+
+nav-days = Días
+nav-week = Semana
+nav-month = Mes
+nav-search = Buscar
+nav-stats = Estadísticas
+nav-settings = Ajustes
+profile-default = Perfil por defecto
+
+window-minimize = Minimizar
+window-maximize = Maximizar
+window-close = Cerrar
+
+## Days
+
+app-title = Ritual
+add-day = Añadir día
+save = Guardar
+new-habit-placeholder = Nuevo hábito
+habit-color-reset = Restablecer
+habit-note-placeholder = Nota
+
+conflict-message = Otro programa ha modificado la base de datos mientras tenías cambios sin guardar
+conflict-keep-local = Quedarme con los míos
+conflict-use-external = Usar los suyos
+
+mood = Ánimo
+journal-add = Escribir en el diario
+journal-edit = Editar diario
+journal-placeholder = ¿Qué tal ha ido el día? Se puede usar Markdown
+
+habit-not-found = Este hábito no existe
+habit-archived = Archivado, ya no se añade a los días nuevos
+habit-archive = Archivar
+habit-unarchive = Desarchivar
+habit-notes = Notas
+habit-strength = Fuerza
+
+grid-previous = Anterior
+grid-next = Siguiente
+
+search-title = Buscar
+search-placeholder = Hábitos, notas y diarios
+search-hint = Filtra con from:2025-01-01, to:2025-01-31, is:done o is:not-done, y pon las "frases exactas" entre comillas.
+search-no-results = No se ha encontrado nada
+search-journal = Diario
+
+stats-title = Estadísticas
+stats-habit = Hábito
+stats-done = Hechos
+stats-completion = Cumplimiento
+stats-current-streak = Racha
+stats-longest-streak = Racha más larga
+stats-trends-title = Tendencias
+stats-trend-completion = Cumplimiento por semana
+stats-trend-per-week = Hábitos cumplidos por semana
+stats-trend-per-weekday = Hábitos cumplidos por día de la semana
+stats-mood-title = Ánimo
+stats-mood-rated = { $days ->
+    [one] Un día valorado.
+   *[other] { $days } días valorados.
+}
+stats-mood-positive = Los mejores días suelen ser en los que cumples más hábitos.
+stats-mood-negative = Los mejores días suelen ser en los que cumples menos hábitos.
+stats-mood-none = El ánimo no parece depender de cuántos hábitos cumples.
+stats-mood-unknown = Valora algunos días más para ver cómo se relaciona el ánimo con tus hábitos.
+stats-mood-when-done = Ánimo al cumplirlo
+stats-mood-when-not-done = Ánimo si no
+
+pause = Pausar
+pause-all-habits = Todos los hábitos
+pause-until = Último día de pausa
+pause-all = Todos los hábitos están en pausa, último día: { $end }
+pause-habit = { $habit } está en pausa, último día: { $end }
+pause-resume = Reanudar
+
+unlock-title = Desbloquear
+unlock-placeholder = Contraseña
+unlock = Desbloquear
+unlock-failed = Contraseña incorrecta
+
+reminder-body = Programado para las { $time }, aún sin hacer
+
+## Settings
+
+settings-title = Ajustes
+settings-db-path = Ruta de la base de datos del perfil por defecto
+settings-timezone = Zona horaria
+settings-timezone-placeholder = Zona horaria del sistema
+settings-day-rollover-hour = Hora de cambio de día
+settings-week-start = La semana empieza el
+settings-theme = Tema
+settings-locale = Idioma
+settings-autosave-interval = Intervalo de autoguardado (segundos, 0 para desactivar)
+settings-shortcuts = Atajos
+settings-shortcut-new-day = Nuevo día
+settings-shortcut-save = Guardar
+settings-shortcut-home = Ir a días
+settings-shortcut-settings = Ir a ajustes
+settings-api = API HTTP
+settings-api-enabled = Activar la API local
+settings-api-port = Puerto
+settings-api-token = Token de acceso
+settings-api-token-generate = Generar
+settings-history = Guardar un historial git de la base de datos
+settings-sync = Sincronización
+settings-sync-server = Servidor de sincronización
+settings-sync-interval = Intervalo de sincronización (segundos, 0 para solo sincronizar a mano)
+settings-daily-notes = Notas diarias
+settings-daily-notes-folder = Carpeta de las notas diarias en Markdown
+settings-daily-notes-template = Plantilla del bloque escrito en cada nota
+settings-save = Guardar ajustes
+settings-saved = Guardado
+settings-saved-restart = Guardado, reinicia para aplicar los cambios
+settings-save-failed = Error al guardar: { $error }
+
+theme-light = Claro
+theme-dark = Oscuro
+theme-system = Sistema
+
+status-pending = Pendiente
+status-done = Hecho
+status-skipped = Saltado
+status-failed = Fallado
+locale-system = Sistema
+
+error-db-path-empty = La ruta de la base de datos no puede estar vacía
+error-db-path-dir = La ruta de la base de datos es un directorio
+error-rollover-hour = Debe ser una hora entre 0 y 23
+error-shortcut-taken = { $shortcut } ya está asignado a otra acción
+error-shortcut-invalid = { $shortcut } no es un atajo válido
+error-timezone = Zona horaria desconocida { $timezone }
+error-number = Debe ser un número entero
+error-weekday = Día de la semana desconocido
+error-theme = Tema desconocido
+error-locale = Idioma desconocido
+error-port = Debe ser un puerto entre 1 y 65535
+error-api-token = Hace falta un token para activar la API
+error-profile-unknown = Perfil desconocido { $profile }
+error-db-failed = No se pudo abrir la base de datos: { $error }
+error-daily-notes-folder = La carpeta de notas diarias es un archivo
@@ -0,0 +1,407 @@
+//! Local HTTP API for scripts, Stream Deck buttons and dashboards.
+//!
+//! The server only forwards requests to the running app, which answers them from
+//! its state and applies changes through the same [`RitualCmd`] channel as the UI,
+//! so they show up live. Until its database is unlocked, the app has no state to
+//! answer from, so requests are refused with `423 Locked`.
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+use crate::config::{ApiConfig, Calendar};
+use crate::db;
+use crate::ics;
+use crate::prelude::*;
+use crate::stats::{self, HabitStats, MoodStats};
+use crate::types::{Day, Habit, HabitStatus};
+use crate::RitualCmd;
+
+/// A request from the server for the app to answer
+#[derive(Debug)]
+pub enum ApiRequest {
+    Habits {
+        reply: oneshot::Sender<Vec<Habit>>,
+    },
+    Today {
+        reply: oneshot::Sender<Option<Day>>,
+    },
+    /// Flips a habit on today's day, replying with whether it is done now
+    Toggle {
+        habit_id: Uuid,
+        reply: oneshot::Sender<Option<bool>>,
+    },
+    Stats {
+        reply: oneshot::Sender<Vec<HabitStats>>,
+    },
+    Mood {
+        reply: oneshot::Sender<MoodStats>,
+    },
+    /// The schedule and history as an iCalendar file
+    Calendar {
+        reply: oneshot::Sender<String>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct Toggled {
+    habit_id: Uuid,
+    done: bool,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    token: String,
+    requests: mpsc::UnboundedSender<ApiRequest>,
+    unlocked: Arc<AtomicBool>,
+}
+
+impl ApiState {
+    async fn ask<T>(
+        &self,
+        request: impl FnOnce(oneshot::Sender<T>) -> ApiRequest,
+    ) -> std::result::Result<T, StatusCode> {
+        let (reply, response) = oneshot::channel();
+        self.requests
+            .send(request(reply))
+            .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+        response.await.map_err(|_| StatusCode::SERVICE_UNAVAILABLE)
+    }
+}
+
+/// Serves the API on localhost until the app exits, answering only while
+/// `unlocked` is set
+pub async fn serve(
+    config: ApiConfig,
+    requests: mpsc::UnboundedSender<ApiRequest>,
+    unlocked: Arc<AtomicBool>,
+) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, config.port)).await?;
+    info!("API listening on http://{}", listener.local_addr()?);
+    let closed = requests.clone();
+    let state = ApiState {
+        token: config.token,
+        requests,
+        unlocked,
+    };
+    // Stop once the app no longer answers, so the next one can take the port
+    axum::serve(listener, router(state))
+        .with_graceful_shutdown(async move { closed.closed().await })
+        .await?;
+    Ok(())
+}
+
+fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/habits", get(habits))
+        .route("/today", get(today))
+        .route("/today/{habit_id}/toggle", post(toggle))
+        .route("/stats", get(stats))
+        .route("/stats/mood", get(mood))
+        .route("/calendar.ics", get(calendar))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_unlocked,
+        ))
+        .layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Takes the token from the `Authorization` header, or from a `token` query
+/// parameter for calendar apps that subscribe to a plain URL
+async fn authorize(
+    State(state): State<ApiState>,
+    Query(query): Query<TokenQuery>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or(query.token.as_deref());
+    // Compared in constant time so response times don't give the token away
+    let authorized =
+        token.is_some_and(|token| bool::from(token.as_bytes().ct_eq(state.token.as_bytes())));
+    if !authorized {
+        warn!(
+            "Rejected unauthorized API request to {}",
+            request.uri().path()
+        );
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    next.run(request).await
+}
+
+async fn require_unlocked(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    if !state.unlocked.load(Ordering::Relaxed) {
+        return StatusCode::LOCKED.into_response();
+    }
+    next.run(request).await
+}
+
+async fn habits(
+    State(state): State<ApiState>,
+) -> std::result::Result<Json<Vec<Habit>>, StatusCode> {
+    state
+        .ask(|reply| ApiRequest::Habits { reply })
+        .await
+        .map(Json)
+}
+
+async fn today(State(state): State<ApiState>) -> std::result::Result<Json<Day>, StatusCode> {
+    state
+        .ask(|reply| ApiRequest::Today { reply })
+        .await?
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn toggle(
+    State(state): State<ApiState>,
+    Path(habit_id): Path<Uuid>,
+) -> std::result::Result<Json<Toggled>, StatusCode> {
+    let done = state
+        .ask(|reply| ApiRequest::Toggle { habit_id, reply })
+        .await?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(Toggled { habit_id, done }))
+}
+
+async fn stats(
+    State(state): State<ApiState>,
+) -> std::result::Result<Json<Vec<HabitStats>>, StatusCode> {
+    state
+        .ask(|reply| ApiRequest::Stats { reply })
+        .await
+        .map(Json)
+}
+
+async fn mood(State(state): State<ApiState>) -> std::result::Result<Json<MoodStats>, StatusCode> {
+    state
+        .ask(|reply| ApiRequest::Mood { reply })
+        .await
+        .map(Json)
+}
+
+async fn calendar(State(state): State<ApiState>) -> std::result::Result<Response, StatusCode> {
+    let ics = state.ask(|reply| ApiRequest::Calendar { reply }).await?;
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        ics,
+    )
+        .into_response())
+}
+
+/// Answers a request from the app's state, passing changes to `send`. Replies are
+/// dropped if the client has gone away in the meantime.
+pub fn handle(
+    request: ApiRequest,
+    state: &db::State,
+    calendar: &Calendar,
+    send: impl FnOnce(RitualCmd),
+) {
+    let today = calendar.today();
+    match request {
+        ApiRequest::Habits { reply } => {
+            let _ = reply.send(state.habits.values().cloned().collect());
+        }
+        ApiRequest::Today { reply } => {
+            let _ = reply.send(state.day_on(today, calendar).cloned());
+        }
+        ApiRequest::Toggle { habit_id, reply } => {
+            let toggled = state.day_on(today, calendar).and_then(|day| {
+                let done = day.habits.get(&habit_id)?.status != HabitStatus::Done;
+                send(RitualCmd::HabitSetDone {
+                    day_id: day.id,
+                    habit_id,
+                    done,
+                });
+                Some(done)
+            });
+            let _ = reply.send(toggled);
+        }
+        ApiRequest::Stats { reply } => {
+            let _ = reply.send(stats::habit_stats(state, calendar, today));
+        }
+        ApiRequest::Mood { reply } => {
+            let _ = reply.send(stats::mood_stats(state, calendar));
+        }
+        ApiRequest::Calendar { reply } => {
+            let _ = reply.send(ics::export(state, calendar, chrono::Utc::now()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    use crate::utils::{NonEmpty, Validate};
+
+    const TOKEN: &str = "secret";
+
+    /// A router answering from a state with one day and one habit, as the app
+    /// would, and the commands it sent
+    fn app() -> (Router, Uuid, std::sync::mpsc::Receiver<RitualCmd>) {
+        let calendar = Calendar {
+            timezone: Some(chrono_tz::UTC),
+            rollover_hour: 0,
+        };
+        let mut state = db::State::default();
+        state.add_day(chrono::Utc::now(), &calendar).unwrap();
+        let day_id = *state.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Read".to_string()).unwrap();
+        state.add_habit_to_day(title, day_id).unwrap();
+        let habit_id = *state.habits.keys().next().unwrap();
+
+        let (requests, mut incoming) = mpsc::unbounded_channel();
+        let (sent, cmds) = std::sync::mpsc::channel();
+        tokio::spawn(async move {
+            while let Some(request) = incoming.recv().await {
+                handle(request, &state, &calendar, |cmd| sent.send(cmd).unwrap());
+            }
+        });
+        let router = router(ApiState {
+            token: TOKEN.to_string(),
+            requests,
+            unlocked: Arc::new(AtomicBool::new(true)),
+        });
+        (router, habit_id, cmds)
+    }
+
+    async fn call(router: &Router, method: &str, uri: &str, token: Option<&str>) -> Response {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        router
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn json(response: Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn requires_token() {
+        let (router, _, _) = app();
+        for token in [None, Some("wrong")] {
+            let response = call(&router, "GET", "/habits", token).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+        let response = call(&router, "GET", "/habits?token=wrong", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = call(&router, "GET", "/habits", Some(TOKEN)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        // Query parameters are URL-decoded, `%74` being a `t`
+        let response = call(&router, "GET", "/habits?other=1&token=secre%74", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn answers_from_state() {
+        let (router, habit_id, _) = app();
+        let habits = json(call(&router, "GET", "/habits", Some(TOKEN)).await).await;
+        assert_eq!(habits[0]["title"], "Read");
+        let today = json(call(&router, "GET", "/today", Some(TOKEN)).await).await;
+        assert_eq!(today["habits"][habit_id.to_string()]["status"], "pending");
+        let stats = json(call(&router, "GET", "/stats", Some(TOKEN)).await).await;
+        assert_eq!(stats[0]["scheduled"], 1);
+
+        let response = call(
+            &router,
+            "GET",
+            &format!("/calendar.ics?token={TOKEN}"),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/calendar; charset=utf-8"
+        );
+    }
+
+    #[tokio::test]
+    async fn toggles_through_commands() {
+        let (router, habit_id, cmds) = app();
+        let uri = format!("/today/{habit_id}/toggle");
+        let toggled = json(call(&router, "POST", &uri, Some(TOKEN)).await).await;
+        assert_eq!(toggled["done"], true);
+        assert!(matches!(
+            cmds.try_recv().unwrap(),
+            RitualCmd::HabitSetDone { done: true, .. }
+        ));
+
+        let uri = format!("/today/{}/toggle", Uuid::new_v4());
+        let response = call(&router, "POST", &uri, Some(TOKEN)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(cmds.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn unavailable_without_app() {
+        let (requests, incoming) = mpsc::unbounded_channel();
+        drop(incoming);
+        let router = router(ApiState {
+            token: TOKEN.to_string(),
+            requests,
+            unlocked: Arc::new(AtomicBool::new(true)),
+        });
+        let response = call(&router, "GET", "/habits", Some(TOKEN)).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn locked_until_unlocked() {
+        let (requests, mut incoming) = mpsc::unbounded_channel::<ApiRequest>();
+        let unlocked = Arc::new(AtomicBool::new(false));
+        let router = router(ApiState {
+            token: TOKEN.to_string(),
+            requests,
+            unlocked: unlocked.clone(),
+        });
+        let uri = format!("/today/{}/toggle", Uuid::new_v4());
+        let response = call(&router, "POST", &uri, Some(TOKEN)).await;
+        assert_eq!(response.status(), StatusCode::LOCKED);
+        // Nothing reached the app
+        assert!(incoming.try_recv().is_err());
+        // Still unauthorized first, so the lock says nothing to strangers
+        let response = call(&router, "GET", "/habits", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        unlocked.store(true, Ordering::Relaxed);
+        tokio::spawn(async move {
+            if let Some(ApiRequest::Habits { reply }) = incoming.recv().await {
+                let _ = reply.send(Vec::new());
+            }
+        });
+        let response = call(&router, "GET", "/habits", Some(TOKEN)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,155 @@
+//! Relay that replicas of ritual sync through.
+//!
+//! It never looks inside the operations, it only keeps all of them in a file and
+//! hands each replica the ones it has not seen yet.
+
+use clap::Parser;
+use color_eyre::eyre;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+#[allow(dead_code)]
+#[path = "../sync/protocol.rs"]
+mod protocol;
+
+use protocol::{ClientMessage, Op, ServerMessage, VectorClock};
+
+type Result<T> = color_eyre::Result<T>;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:7465")]
+    listen: String,
+
+    /// File the operations are kept in
+    #[arg(long)]
+    data: PathBuf,
+}
+
+#[derive(Debug, Default, Serialize, serde::Deserialize)]
+struct Store {
+    clock: VectorClock,
+    ops: Vec<Op<Value>>,
+}
+
+impl Store {
+    fn load(path: &PathBuf) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    fn save(&self, path: &PathBuf) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    fn missing(&self, clock: &VectorClock) -> Vec<Op<Value>> {
+        self.ops
+            .iter()
+            .filter(|op| !clock.contains(op))
+            .cloned()
+            .collect()
+    }
+
+    /// Adds the operations that are new, returning how many there were
+    fn add(&mut self, ops: Vec<Op<Value>>) -> usize {
+        let mut added = 0;
+        for op in ops {
+            if self.clock.contains(&op) {
+                continue;
+            }
+            self.clock.observe(&op);
+            self.ops.push(op);
+            added += 1;
+        }
+        added
+    }
+}
+
+async fn send<T: Serialize>(
+    writer: &mut tokio::net::tcp::OwnedWriteHalf,
+    message: &T,
+) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    Ok(())
+}
+
+async fn handle(stream: TcpStream, store: Arc<Mutex<Store>>, path: Arc<PathBuf>) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut next = async || -> Result<ClientMessage<Value>> {
+        let line = lines
+            .next_line()
+            .await?
+            .ok_or_else(|| eyre::eyre!("Client closed the connection"))?;
+        Ok(serde_json::from_str(&line)?)
+    };
+
+    let ClientMessage::Hello { clock } = next().await? else {
+        return Err(eyre::eyre!("Expected a hello"));
+    };
+    let (server_clock, missing) = {
+        let store = store.lock().await;
+        (store.clock.clone(), store.missing(&clock))
+    };
+    let sent = missing.len();
+    send(
+        &mut writer,
+        &ServerMessage::Missing {
+            clock: server_clock,
+            ops: missing,
+        },
+    )
+    .await?;
+
+    let ClientMessage::Push { ops } = next().await? else {
+        return Err(eyre::eyre!("Expected a push"));
+    };
+    let added = {
+        let mut store = store.lock().await;
+        let added = store.add(ops);
+        if added > 0 {
+            store.save(&path)?;
+        }
+        added
+    };
+    info!("Sent {} operations, received {} new ones", sent, added);
+    send(&mut writer, &ServerMessage::<Value>::Done).await
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    color_eyre::install()?;
+    tracing_subscriber::fmt().init();
+    let args = Args::parse();
+
+    let store = Arc::new(Mutex::new(Store::load(&args.data)?));
+    let path = Arc::new(args.data);
+    let listener = TcpListener::bind(&args.listen).await?;
+    info!("Sync server listening on {}", listener.local_addr()?);
+    loop {
+        let (stream, addr) = listener.accept().await?;
+        info!("Syncing with {}", addr);
+        let (store, path) = (store.clone(), path.clone());
+        tokio::spawn(async move {
+            if let Err(err) = handle(stream, store, path).await {
+                error!("Failed to sync with {}: {}", addr, err);
+            }
+        });
+    }
+}
@@ -1,20 +1,179 @@
-use clap::Parser;
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use color_eyre::eyre;
+use std::path::{Path, PathBuf};
 use tracing::Level;
 
+use crate::config::{self, Config, ConfigOverrides};
+use crate::crypto::Passphrase;
+use crate::merge;
+use crate::prelude::*;
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct RawArgs {
     #[arg(long, short = 'v', global = true, action = clap::ArgAction::Count)]
     verbosity: u8,
+
+    /// Path of the config file
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Path of the database, overrides `db_path` from the config file
+    #[arg(long, global = true, conflicts_with = "profile")]
+    db: Option<PathBuf>,
+
+    /// Profile to use instead of the one selected in the config file
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// File holding the passphrase of an encrypted database
+    #[arg(long, global = true)]
+    passphrase_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// Run without a window, only firing habit reminders
+    Daemon {
+        /// Print reminders to stdout instead of showing desktop notifications
+        #[arg(long, conflicts_with = "notify_file")]
+        stdout: bool,
+        /// Append reminders to a file instead of showing desktop notifications
+        #[arg(long)]
+        notify_file: Option<PathBuf>,
+    },
+    /// Add today's day
+    NewDay,
+    /// Add a habit to today's day
+    Add { title: String },
+    /// Mark one of today's habits as done, by title or id
+    Check { habit: String },
+    /// Mark one of today's habits as not done, by title or id
+    Uncheck { habit: String },
+    /// Mark one of today's habits as deliberately skipped, by title or id
+    Skip { habit: String },
+    /// Mark one of today's habits as failed, by title or id
+    Fail { habit: String },
+    /// Write a note on one of today's habits, by title or id. An empty note clears it.
+    Note { habit: String, note: String },
+    /// Stop adding a habit to new days, keeping its history. By title or id.
+    Archive { habit: String },
+    /// Start adding an archived habit to new days again, by title or id
+    Unarchive { habit: String },
+    /// Find habits, notes and journal entries, e.g. `run "by the river" is:done
+    /// from:2025-01-01 to:2025-01-31`
+    Search {
+        #[arg(required = true)]
+        query: Vec<String>,
+    },
+    /// Pause habits, e.g. while on holiday or ill, so they do not break streaks
+    Pause {
+        /// Last paused day, as YYYY-MM-DD
+        #[arg(long)]
+        until: NaiveDate,
+        /// First paused day, today by default
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Only pause this habit, by title or id
+        #[arg(long)]
+        habit: Option<String>,
+    },
+    /// End the pause that is on today
+    Resume {
+        /// End the pause of this habit, by title or id
+        #[arg(long)]
+        habit: Option<String>,
+    },
+    /// Export the habit schedule and done check-ins as an iCalendar file, for
+    /// calendar apps
+    ExportIcs {
+        /// Where to write the file, stdout by default
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Write days into the Markdown daily notes of a vault such as Obsidian's or
+    /// Logseq's, replacing only the block from earlier exports
+    ExportNotes {
+        /// First day to export, as YYYY-MM-DD. Only `--to` by default.
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day to export, today by default
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Folder of the notes, overrides `daily_notes.folder` from the config file
+        #[arg(long)]
+        folder: Option<PathBuf>,
+    },
+    /// Trade changes with other devices through the configured sync server
+    Sync,
+    /// Combine two copies of a database that were edited separately
+    Merge {
+        first: PathBuf,
+        second: PathBuf,
+        /// Where to write the merged database
+        #[arg(long, short)]
+        output: PathBuf,
+        /// What to keep when a habit's status differs between the copies
+        #[arg(long, value_enum, default_value_t)]
+        policy: merge::DonePolicy,
+    },
+    /// List the commits in the database history
+    Log,
+    /// Bring the database back to how it was at a commit of its history, with the
+    /// app closed
+    Restore { rev: String },
+    /// Encrypt the database with the passphrase from `--passphrase-file`
+    Encrypt,
+    /// Store an encrypted database in plain text again
+    Decrypt,
+    /// Change the passphrase of an encrypted database
+    Rekey {
+        /// File holding the new passphrase
+        #[arg(long)]
+        new_passphrase_file: PathBuf,
+    },
+    /// Manage the profiles, each tracking its own habits
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum ProfileCommand {
+    /// List the profiles, marking the one in use
+    List,
+    /// Create a profile
+    Add {
+        name: String,
+        /// Where to keep its database, in the data directory by default
+        #[arg(long)]
+        db: Option<PathBuf>,
+        /// Sync server of the profile
+        #[arg(long)]
+        sync_server: Option<String>,
+    },
+    /// Select the profile the app and commands use from now on
+    Use { name: String },
 }
 
 #[derive(Debug)]
 pub(crate) struct ParsedArgs {
     pub log_level: tracing::Level,
+    pub command: Option<Command>,
+    pub config_path: PathBuf,
+    pub overrides: ConfigOverrides,
+    pub passphrase: Option<Passphrase>,
+    /// The config file merged with the command line overrides
+    pub config: Config,
 }
 
 impl ParsedArgs {
-    pub fn parse_raw() -> Self {
+    pub fn parse_raw() -> Result<Self> {
         let args: RawArgs = clap::Parser::parse();
 
         let log_level = match args.verbosity {
@@ -23,6 +182,46 @@ impl ParsedArgs {
             _ => Level::TRACE,
         };
 
-        ParsedArgs { log_level }
+        let config_path = args.config.unwrap_or_else(config::default_config_path);
+        let overrides = ConfigOverrides {
+            db_path: args.db,
+            profile: args
+                .profile
+                .map(|name| (name != config::DEFAULT_PROFILE).then_some(name)),
+        };
+        let config = overrides.apply(Config::load(&config_path)?);
+        if let Some(profile) = &config.profile {
+            if !config.profiles.contains_key(profile) {
+                return Err(eyre::eyre!(
+                    "Unknown profile {:?}, create it with `ritual profile add`",
+                    profile
+                ));
+            }
+        }
+        let passphrase = args
+            .passphrase_file
+            .as_deref()
+            .map(read_passphrase)
+            .transpose()?;
+
+        Ok(ParsedArgs {
+            log_level,
+            command: args.command,
+            config_path,
+            overrides,
+            passphrase,
+            config,
+        })
+    }
+}
+
+/// Reads a passphrase from the first line of a file
+pub(crate) fn read_passphrase(path: &Path) -> Result<Passphrase> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| eyre::eyre!("Failed to read passphrase from {:?}: {}", path, err))?;
+    let passphrase = contents.lines().next().unwrap_or_default();
+    if passphrase.is_empty() {
+        return Err(eyre::eyre!("Passphrase file {:?} is empty", path));
     }
+    Ok(Passphrase::new(passphrase.to_string()))
 }
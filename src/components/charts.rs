@@ -0,0 +1,117 @@
+use dioxus::prelude::*;
+
+/// Size of the drawing area, the SVG is scaled to fit its container
+const WIDTH: f64 = 300.0;
+const HEIGHT: f64 = 100.0;
+/// Share of each bar's slot left empty between bars
+const BAR_GAP: f64 = 0.2;
+
+#[derive(PartialEq, Props, Clone)]
+pub struct LineChartProps {
+    class: Option<String>,
+    /// Evenly spaced values, from 0 to `max`. The line breaks where one is missing
+    values: Vec<Option<f64>>,
+    #[props(default = 1.0)]
+    max: f64,
+}
+
+#[component]
+pub fn LineChart(props: LineChartProps) -> Element {
+    let step = WIDTH / props.values.len().saturating_sub(1).max(1) as f64;
+    let mut segments: Vec<Vec<String>> = Vec::new();
+    let mut gap = true;
+    for (i, value) in props.values.iter().enumerate() {
+        let Some(value) = value else {
+            gap = true;
+            continue;
+        };
+        let y = HEIGHT - (value / props.max).clamp(0.0, 1.0) * HEIGHT;
+        let point = format!("{:.1},{:.1}", i as f64 * step, y);
+        match segments.last_mut() {
+            Some(segment) if !gap => segment.push(point),
+            _ => segments.push(vec![point]),
+        }
+        gap = false;
+    }
+    // A point on its own is drawn as a dot by its round line cap
+    let segments = segments.into_iter().map(|segment| match &segment[..] {
+        [point] => format!("{point} {point}"),
+        _ => segment.join(" "),
+    });
+    let class = format!("chart line-chart {}", props.class.unwrap_or_default());
+    rsx! {
+        svg {
+            class,
+            view_box: "0 0 {WIDTH} {HEIGHT}",
+            preserve_aspect_ratio: "none",
+            line { class: "axis", x1: 0, y1: HEIGHT, x2: WIDTH, y2: HEIGHT }
+            for points in segments {
+                polyline { points, fill: "none" }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Props, Clone)]
+pub struct BarChartProps {
+    class: Option<String>,
+    values: Vec<f64>,
+    /// Shown under the bars, one per value
+    #[props(default)]
+    labels: Vec<String>,
+    /// Value of a full height bar, the largest value when left out
+    max: Option<f64>,
+}
+
+#[component]
+pub fn BarChart(props: BarChartProps) -> Element {
+    let max = props
+        .max
+        .unwrap_or_else(|| props.values.iter().copied().fold(0.0, f64::max));
+    let slot = WIDTH / props.values.len().max(1) as f64;
+    let bars = props
+        .values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let height = if max > 0.0 {
+                (value / max).clamp(0.0, 1.0) * HEIGHT
+            } else {
+                0.0
+            };
+            let title = match props.labels.get(i) {
+                Some(label) => format!("{label}: {value}"),
+                None => value.to_string(),
+            };
+            (i as f64 * slot, height, title)
+        })
+        .collect::<Vec<_>>();
+    let class = format!("chart bar-chart {}", props.class.unwrap_or_default());
+    rsx! {
+        div {
+            class,
+            svg {
+                view_box: "0 0 {WIDTH} {HEIGHT}",
+                preserve_aspect_ratio: "none",
+                line { class: "axis", x1: 0, y1: HEIGHT, x2: WIDTH, y2: HEIGHT }
+                for (x, height, title) in bars {
+                    rect {
+                        x: x + slot * BAR_GAP / 2.0,
+                        y: HEIGHT - height,
+                        width: slot * (1.0 - BAR_GAP),
+                        height,
+                        title { "{title}" }
+                    }
+                }
+            }
+            if !props.labels.is_empty() {
+                div {
+                    class: "labels",
+                    for label in props.labels.iter() {
+                        span { "{label}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,3 +1,7 @@
 pub mod button;
+pub mod charts;
 pub mod emoji_picker;
 pub mod icon;
+#[cfg(feature = "desktop")]
+pub mod title_bar;
+pub mod virtual_list;
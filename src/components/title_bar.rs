@@ -0,0 +1,100 @@
+use dioxus::desktop::{tao::event::Event, use_window, use_wry_event_handler, WindowEvent};
+use dioxus::prelude::*;
+use dioxus_free_icons::icons::io_icons::{IoClose, IoRemove, IoSquareOutline};
+
+use crate::components::icon::Icon;
+use crate::i18n::t;
+use crate::prelude::*;
+use crate::window_state::WindowState;
+
+/// Delay after the last move or resize before the window state is written to disk
+const SAVE_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(500);
+
+#[component]
+pub fn TitleBar(title: String) -> Element {
+    let window = use_window();
+    let mut generation = use_signal(|| 0u64);
+
+    let save = {
+        let window = window.clone();
+        move || {
+            WindowState::of_window(&window)
+                .save()
+                .unwrap_or_else(|err| error!("Failed to save window state: {}", err));
+        }
+    };
+
+    use_wry_event_handler({
+        let save = save.clone();
+        move |event, _| {
+            let Event::WindowEvent { event, .. } = event else {
+                return;
+            };
+            match event {
+                WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+                    let current = generation() + 1;
+                    generation.set(current);
+                    let save = save.clone();
+                    spawn(async move {
+                        crate::utils::sleep(SAVE_DEBOUNCE).await;
+                        if generation() == current {
+                            save();
+                        }
+                    });
+                }
+                WindowEvent::CloseRequested => save(),
+                _ => {}
+            }
+        }
+    });
+
+    let drag = {
+        let window = window.clone();
+        move |_| window.drag()
+    };
+    let toggle_maximized = {
+        let window = window.clone();
+        move |_| window.toggle_maximized()
+    };
+    let minimize = {
+        let window = window.clone();
+        move |_| window.set_minimized(true)
+    };
+    let close = move |_| {
+        save();
+        window.close();
+    };
+
+    rsx! {
+        div {
+            class: "title-bar",
+            div {
+                class: "drag-region",
+                onmousedown: drag,
+                ondoubleclick: toggle_maximized.clone(),
+                span { class: "title", "{title}" }
+            }
+            div {
+                class: "window-controls",
+                button {
+                    class: "minimize",
+                    title: t!("window-minimize"),
+                    onclick: minimize,
+                    Icon { icon: IoRemove }
+                }
+                button {
+                    class: "maximize",
+                    title: t!("window-maximize"),
+                    onclick: toggle_maximized,
+                    Icon { icon: IoSquareOutline, width: 12, height: 12 }
+                }
+                button {
+                    class: "close",
+                    title: t!("window-close"),
+                    onclick: close,
+                    Icon { icon: IoClose }
+                }
+            }
+        }
+    }
+}
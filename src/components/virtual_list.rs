@@ -0,0 +1,114 @@
+use dioxus::prelude::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Pixels from the end of the list at which more items are asked for
+const END_THRESHOLD: f64 = 200.0;
+
+#[derive(PartialEq, Props, Clone)]
+pub struct VirtualListProps {
+    class: Option<String>,
+    /// One key per item, in order
+    keys: Vec<String>,
+    render: Callback<String, Element>,
+    /// Height to assume for items that have not been shown yet, in pixels
+    estimated_height: f64,
+    /// How many items to render past the visible ones on each side
+    #[props(default = 3)]
+    overscan: usize,
+    /// Called when the list is scrolled close to its end
+    on_end_reached: Option<Callback<()>>,
+}
+
+/// A scrolling list that only renders the items in view. Items can be of any
+/// height, each is measured once it has been rendered.
+#[component]
+pub fn VirtualList(props: VirtualListProps) -> Element {
+    let mut container = use_signal(|| None::<Rc<MountedData>>);
+    let mut heights = use_signal(HashMap::<String, f64>::new);
+    // Scroll offset and height of the visible part of the list
+    let mut viewport = use_signal(|| (0.0, 0.0));
+
+    let tops = props
+        .keys
+        .iter()
+        .scan(0.0, |top, key| {
+            let item_top = *top;
+            *top += heights
+                .read()
+                .get(key)
+                .copied()
+                .unwrap_or(props.estimated_height);
+            Some(item_top)
+        })
+        .collect::<Vec<_>>();
+    let total = tops.last().map_or(0.0, |top| {
+        top + props
+            .keys
+            .last()
+            .and_then(|key| heights.read().get(key).copied())
+            .unwrap_or(props.estimated_height)
+    });
+    let (scroll_top, view_height) = viewport();
+    let first = tops
+        .partition_point(|top| *top <= scroll_top)
+        .saturating_sub(1 + props.overscan);
+    let end = (tops.partition_point(|top| *top < scroll_top + view_height) + props.overscan)
+        .min(props.keys.len());
+    let first = first.min(end);
+    let before = tops.get(first).copied().unwrap_or(0.0);
+    let after = total - tops.get(end).copied().unwrap_or(total);
+
+    let class = format!("virtual-list {}", props.class.unwrap_or_default());
+    let on_end_reached = props.on_end_reached;
+    // Checked again as items come in, so a list shorter than its viewport keeps
+    // asking for more
+    let near_end = view_height > 0.0 && scroll_top + view_height >= total - END_THRESHOLD;
+    let count = props.keys.len();
+    use_effect(use_reactive!(|(near_end, count)| {
+        if near_end && count > 0 {
+            if let Some(on_end_reached) = on_end_reached {
+                on_end_reached.call(());
+            }
+        }
+    }));
+    let measure = move || async move {
+        let Some(container) = container() else {
+            return;
+        };
+        let (Ok(offset), Ok(rect)) = (
+            container.get_scroll_offset().await,
+            container.get_client_rect().await,
+        ) else {
+            return;
+        };
+        viewport.set((offset.y, rect.height()));
+    };
+
+    rsx! {
+        div {
+            class,
+            onmounted: move |e| {
+                container.set(Some(e.data()));
+                measure()
+            },
+            onscroll: move |_| measure(),
+            div { style: "height: {before}px" }
+            for key in props.keys[first..end].iter().cloned() {
+                div {
+                    key: "{key}",
+                    onresize: {
+                        let key = key.clone();
+                        move |e: Event<ResizeData>| {
+                            if let Ok(size) = e.get_border_box_size() {
+                                heights.write().insert(key.clone(), size.height);
+                            }
+                        }
+                    },
+                    {props.render.call(key.clone())}
+                }
+            }
+            div { style: "height: {after}px" }
+        }
+    }
+}
@@ -0,0 +1,751 @@
+use chrono::{Local, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use color_eyre::eyre;
+use dioxus::prelude::{KeyboardData, ModifiersInteraction};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::{t, Locale};
+use crate::prelude::*;
+use crate::storage;
+use crate::types::DateTime;
+use crate::utils::{NonEmpty, Validate};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Database of the default profile
+    pub db_path: PathBuf,
+    /// The profile in use, the default one when unset
+    pub profile: Option<String>,
+    pub profiles: IndexMap<String, Profile>,
+    /// IANA timezone name, the system timezone is used when unset
+    pub timezone: Option<Tz>,
+    /// Hour of the day (0-23) before which it still counts as the previous day
+    pub day_rollover_hour: u32,
+    pub week_start: Weekday,
+    pub theme: Theme,
+    pub shortcuts: Shortcuts,
+    /// Seconds between automatic saves, 0 disables autosave
+    pub autosave_interval: u64,
+    /// UI language, the system language is used when unset
+    pub locale: Option<Locale>,
+    pub api: ApiConfig,
+    pub sync: SyncConfig,
+    pub daily_notes: DailyNotesConfig,
+    /// Commit the database into a git repository next to it on every save
+    pub history: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            db_path: data_dir().join("db.json"),
+            profile: None,
+            profiles: IndexMap::new(),
+            timezone: None,
+            day_rollover_hour: 0,
+            week_start: Weekday::Mon,
+            theme: Theme::default(),
+            shortcuts: Shortcuts::default(),
+            autosave_interval: 60,
+            locale: None,
+            api: ApiConfig::default(),
+            sync: SyncConfig::default(),
+            daily_notes: DailyNotesConfig::default(),
+            history: false,
+        }
+    }
+}
+
+/// Name the profile that uses `db_path` goes by
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// A separate tracker with its own database, such as one for work habits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub db_path: PathBuf,
+    /// Sync server of this profile. Servers hand out every operation they have,
+    /// so profiles never share the default profile's.
+    #[serde(default)]
+    pub sync_server: Option<String>,
+}
+
+impl Profile {
+    /// A profile with its database in the data directory, named after the profile.
+    /// Names become file names, so they cannot hold path separators or be `.` or
+    /// `..`.
+    #[cfg(not(feature = "web"))]
+    pub fn new(name: &str) -> Result<Self> {
+        let valid = !matches!(name, "" | "." | "..")
+            && !name.contains(['/', '\\'])
+            && !name.chars().any(char::is_control);
+        if !valid {
+            return Err(eyre::eyre!("Invalid profile name {:?}", name));
+        }
+        Ok(Self {
+            db_path: data_dir().join("profiles").join(format!("{}.json", name)),
+            sync_server: None,
+        })
+    }
+}
+
+/// Syncing with other devices through a `ritual-sync-server`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// `host:port` of the sync server, syncing is off when unset
+    pub server: Option<String>,
+    /// Seconds between automatic syncs, 0 only syncs on demand
+    pub interval: u64,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            server: None,
+            interval: 300,
+        }
+    }
+}
+
+/// Exporting days into the daily notes of a Markdown vault, like Obsidian's or
+/// Logseq's
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyNotesConfig {
+    /// Folder holding the `YYYY-MM-DD.md` notes
+    pub folder: Option<PathBuf>,
+    /// What goes in the block written into each note, with `{date}`, `{habits}`,
+    /// `{mood}` and `{journal}` filled in
+    pub template: String,
+}
+
+impl Default for DailyNotesConfig {
+    fn default() -> Self {
+        Self {
+            folder: None,
+            template: "## Habits\n\n{habits}".to_string(),
+        }
+    }
+}
+
+/// The local HTTP API for scripts and other integrations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    /// Port on localhost to listen on
+    pub port: u16,
+    /// Clients authenticate with `Authorization: Bearer <token>`, or with
+    /// `?token=<token>` where headers cannot be set, like calendar subscriptions
+    pub token: String,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7464,
+            token: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+    #[default]
+    System,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::System];
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Theme::Light => write!(f, "light"),
+            Theme::Dark => write!(f, "dark"),
+            Theme::System => write!(f, "system"),
+        }
+    }
+}
+
+impl std::str::FromStr for Theme {
+    type Err = eyre::Report;
+    fn from_str(s: &str) -> Result<Self> {
+        Theme::ALL
+            .into_iter()
+            .find(|t| t.to_string() == s)
+            .ok_or_else(|| eyre::eyre!("Unknown theme {}", s))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Shortcuts {
+    pub new_day: String,
+    pub save: String,
+    pub home: String,
+    pub settings: String,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self {
+            new_day: "ctrl+n".to_string(),
+            save: "ctrl+s".to_string(),
+            home: "ctrl+h".to_string(),
+            settings: "ctrl+,".to_string(),
+        }
+    }
+}
+
+impl Shortcuts {
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &String)> {
+        [
+            ("shortcuts.new_day", &self.new_day),
+            ("shortcuts.save", &self.save),
+            ("shortcuts.home", &self.home),
+            ("shortcuts.settings", &self.settings),
+        ]
+        .into_iter()
+    }
+}
+
+/// A key combination such as `ctrl+shift+s`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcut {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+    pub key: String,
+}
+
+impl std::str::FromStr for Shortcut {
+    type Err = eyre::Report;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut shortcut = Shortcut {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            meta: false,
+            key: String::new(),
+        };
+        let mut parts = s.split('+').peekable();
+        while let Some(part) = parts.next() {
+            let part = part.trim().to_lowercase();
+            if parts.peek().is_none() {
+                let key = NonEmpty::new_validated(part)
+                    .map_err(|_| eyre::eyre!("Shortcut {:?} has no key", s))?;
+                shortcut.key = key.inner();
+                break;
+            }
+            match part.as_str() {
+                "ctrl" => shortcut.ctrl = true,
+                "alt" => shortcut.alt = true,
+                "shift" => shortcut.shift = true,
+                "meta" | "super" => shortcut.meta = true,
+                other => return Err(eyre::eyre!("Unknown modifier {:?}", other)),
+            }
+        }
+        Ok(shortcut)
+    }
+}
+
+impl Shortcut {
+    pub fn matches(&self, e: &KeyboardData) -> bool {
+        let modifiers = e.modifiers();
+        modifiers.ctrl() == self.ctrl
+            && modifiers.alt() == self.alt
+            && modifiers.shift() == self.shift
+            && modifiers.meta() == self.meta
+            && e.key().to_string().to_lowercase() == self.key
+    }
+}
+
+/// A setting that failed validation, shown next to its field in the settings route
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Turns timestamps into the dates they belong to, honoring the configured
+/// timezone and day rollover hour
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calendar {
+    pub timezone: Option<Tz>,
+    pub rollover_hour: u32,
+}
+
+impl Calendar {
+    pub fn date_of(&self, t: DateTime) -> NaiveDate {
+        let shifted = t - chrono::Duration::hours(self.rollover_hour as i64);
+        match self.timezone {
+            Some(tz) => shifted.with_timezone(&tz).date_naive(),
+            None => shifted.with_timezone(&Local).date_naive(),
+        }
+    }
+
+    pub fn time_of(&self, t: DateTime) -> NaiveTime {
+        match self.timezone {
+            Some(tz) => t.with_timezone(&tz).time(),
+            None => t.with_timezone(&Local).time(),
+        }
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.date_of(chrono::Utc::now())
+    }
+}
+
+#[cfg(not(feature = "web"))]
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ritual")
+}
+
+#[cfg(not(feature = "web"))]
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ritual")
+}
+
+// In the browser paths are only keys into localStorage
+#[cfg(feature = "web")]
+pub fn config_dir() -> PathBuf {
+    PathBuf::from("ritual")
+}
+
+#[cfg(feature = "web")]
+pub fn data_dir() -> PathBuf {
+    PathBuf::from("ritual")
+}
+
+/// Where the database was kept before it moved into the data directory
+#[cfg(not(feature = "web"))]
+const LEGACY_DB_PATH: &str = "/tmp/db.json";
+
+/// Copies the database older versions kept in `/tmp` to the default location, when
+/// it is in use and has no database yet. The old file is left in place.
+#[cfg(not(feature = "web"))]
+pub fn migrate_legacy_db(config: &Config) {
+    let db_path = config.database();
+    if db_path != Config::default().db_path {
+        return;
+    }
+    match migrate_db(Path::new(LEGACY_DB_PATH), &db_path) {
+        Ok(false) => {}
+        Ok(true) => info!(
+            "Copied the database from {} to {}",
+            LEGACY_DB_PATH,
+            db_path.display()
+        ),
+        Err(err) => warn!(
+            "Your previous database is still at {}: {:#}",
+            LEGACY_DB_PATH, err
+        ),
+    }
+}
+
+/// Copies `legacy` to `db_path` if only the former exists, returning whether it did
+#[cfg(not(feature = "web"))]
+fn migrate_db(legacy: &Path, db_path: &Path) -> Result<bool> {
+    use color_eyre::eyre::WrapErr;
+
+    if db_path.exists() || !legacy.is_file() {
+        return Ok(false);
+    }
+    if let Some(dir) = db_path.parent() {
+        std::fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::copy(legacy, db_path)
+        .wrap_err_with(|| format!("Failed to copy it to {}", db_path.display()))?;
+    Ok(true)
+}
+
+pub fn default_config_path() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+impl Config {
+    /// Loads the config file, falling back to the defaults if it does not exist
+    #[tracing::instrument]
+    pub fn load(path: &Path) -> Result<Self> {
+        if !storage::exists(path) {
+            info!("No config file at {:?}, using defaults", path);
+            return Ok(Self::default());
+        }
+        info!("Loading config from {:?}", path);
+        let contents = storage::read_to_string(path)?;
+        let config: Config = toml::from_str(&contents)?;
+        if let Some(err) = config.validate().into_iter().next() {
+            return Err(eyre::eyre!("Invalid config {:?}: {}", path, err));
+        }
+        Ok(config)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn save(&self, path: &Path) -> Result<()> {
+        info!("Saving config to {:?}", path);
+        storage::write(path, &toml::to_string_pretty(self)?)
+    }
+
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        if self.db_path.as_os_str().is_empty() {
+            errors.push(ConfigError {
+                field: "db_path",
+                message: t!("error-db-path-empty"),
+            });
+        } else if self.db_path.is_dir() {
+            errors.push(ConfigError {
+                field: "db_path",
+                message: t!("error-db-path-dir"),
+            });
+        }
+        if let Some(profile) = &self.profile {
+            if !self.profiles.contains_key(profile) {
+                errors.push(ConfigError {
+                    field: "profile",
+                    message: t!("error-profile-unknown", profile = profile.clone()),
+                });
+            }
+        }
+        if self.day_rollover_hour > 23 {
+            errors.push(ConfigError {
+                field: "day_rollover_hour",
+                message: t!("error-rollover-hour"),
+            });
+        }
+        if self.api.port == 0 {
+            errors.push(ConfigError {
+                field: "api.port",
+                message: t!("error-port"),
+            });
+        }
+        if self
+            .daily_notes
+            .folder
+            .as_ref()
+            .is_some_and(|folder| folder.is_file())
+        {
+            errors.push(ConfigError {
+                field: "daily_notes.folder",
+                message: t!("error-daily-notes-folder"),
+            });
+        }
+        if self.api.enabled && self.api.token.trim().is_empty() {
+            errors.push(ConfigError {
+                field: "api.token",
+                message: t!("error-api-token"),
+            });
+        }
+        let mut seen = Vec::new();
+        for (field, shortcut) in self.shortcuts.iter() {
+            match shortcut.parse::<Shortcut>() {
+                Ok(parsed) if seen.contains(&parsed) => errors.push(ConfigError {
+                    field,
+                    message: t!("error-shortcut-taken", shortcut = shortcut.clone()),
+                }),
+                Ok(parsed) => seen.push(parsed),
+                Err(err) => {
+                    trace!("Invalid shortcut {:?}: {}", shortcut, err);
+                    errors.push(ConfigError {
+                        field,
+                        message: t!("error-shortcut-invalid", shortcut = shortcut.clone()),
+                    })
+                }
+            }
+        }
+        errors
+    }
+
+    /// Database of the profile in use
+    pub fn database(&self) -> PathBuf {
+        match self.active_profile() {
+            Some(profile) => profile.db_path.clone(),
+            None => self.db_path.clone(),
+        }
+    }
+
+    pub fn sync_server(&self) -> Option<String> {
+        match self.active_profile() {
+            Some(profile) => profile.sync_server.clone(),
+            None => self.sync.server.clone(),
+        }
+    }
+
+    fn active_profile(&self) -> Option<&Profile> {
+        self.profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+    }
+
+    /// Makes `profile` the one used from now on, `None` being the default profile
+    #[tracing::instrument]
+    pub fn save_profile(path: &Path, profile: Option<String>) -> Result<()> {
+        let mut config = Self::load(path)?;
+        if profile
+            .as_ref()
+            .is_some_and(|name| !config.profiles.contains_key(name))
+        {
+            return Err(eyre::eyre!("Unknown profile {:?}", profile));
+        }
+        config.profile = profile;
+        config.save(path)
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale.unwrap_or_else(Locale::from_system)
+    }
+
+    pub fn calendar(&self) -> Calendar {
+        Calendar {
+            timezone: self.timezone,
+            rollover_hour: self.day_rollover_hour,
+        }
+    }
+}
+
+/// Where the running app's config came from, so the settings route can write it back
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigSource {
+    pub path: PathBuf,
+    pub overrides: ConfigOverrides,
+}
+
+impl ConfigSource {
+    /// Stylesheet next to the config file that is applied on top of the theme
+    pub fn user_css_path(&self) -> PathBuf {
+        self.path.with_file_name("user.css")
+    }
+}
+
+/// Settings given on the command line, which take precedence over the config file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigOverrides {
+    pub db_path: Option<PathBuf>,
+    /// `Some(None)` picks the default profile whatever the config file says
+    pub profile: Option<Option<String>>,
+}
+
+impl ConfigOverrides {
+    pub fn apply(&self, mut config: Config) -> Config {
+        if let Some(profile) = &self.profile {
+            config.profile = profile.clone();
+        }
+        // A database given explicitly is used as is, without any profile
+        if let Some(db_path) = &self.db_path {
+            config.db_path = db_path.clone();
+            config.profile = None;
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(config: &Config) -> Vec<&'static str> {
+        config.validate().into_iter().map(|e| e.field).collect()
+    }
+
+    #[test]
+    fn parses_shortcuts() {
+        let shortcut: Shortcut = "Ctrl + Shift + S".parse().unwrap();
+        assert!(shortcut.ctrl && shortcut.shift && !shortcut.alt && !shortcut.meta);
+        assert_eq!(shortcut.key, "s");
+        let shortcut: Shortcut = "super+,".parse().unwrap();
+        assert!(shortcut.meta);
+        assert_eq!(shortcut.key, ",");
+        assert_eq!("n".parse::<Shortcut>().unwrap().key, "n");
+        assert!("ctrl+".parse::<Shortcut>().is_err());
+        assert!("hyper+n".parse::<Shortcut>().is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "web"))]
+    fn migrates_legacy_db() {
+        let dir = std::env::temp_dir().join(format!("ritual-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let legacy = dir.join("legacy.json");
+        let db_path = dir.join("data").join("db.json");
+        assert!(!migrate_db(&legacy, &db_path).unwrap());
+
+        std::fs::write(&legacy, "{}").unwrap();
+        assert!(migrate_db(&legacy, &db_path).unwrap());
+        assert_eq!(std::fs::read_to_string(&db_path).unwrap(), "{}");
+        assert!(legacy.exists());
+        // An existing database is never overwritten
+        std::fs::write(&legacy, "old").unwrap();
+        assert!(!migrate_db(&legacy, &db_path).unwrap());
+        assert_eq!(std::fs::read_to_string(&db_path).unwrap(), "{}");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn validates() {
+        assert_eq!(fields(&Config::default()), Vec::<&str>::new());
+        let config = Config {
+            db_path: PathBuf::new(),
+            profile: Some("work".to_string()),
+            day_rollover_hour: 24,
+            api: ApiConfig {
+                enabled: true,
+                port: 0,
+                token: " ".to_string(),
+            },
+            shortcuts: Shortcuts {
+                save: "ctrl+n".to_string(),
+                home: "hyper+h".to_string(),
+                ..Shortcuts::default()
+            },
+            ..Config::default()
+        };
+        assert_eq!(
+            fields(&config),
+            [
+                "db_path",
+                "profile",
+                "day_rollover_hour",
+                "api.port",
+                "api.token",
+                "shortcuts.save",
+                "shortcuts.home",
+            ]
+        );
+        let config = Config {
+            db_path: std::env::temp_dir(),
+            ..Config::default()
+        };
+        assert_eq!(fields(&config), ["db_path"]);
+    }
+
+    #[test]
+    fn dates_roll_over() {
+        let calendar = Calendar {
+            timezone: Some(chrono_tz::Europe::Madrid),
+            rollover_hour: 4,
+        };
+        let date = |t: &str| calendar.date_of(t.parse().unwrap()).to_string();
+        // 02:30 in Madrid still counts as the day before
+        assert_eq!(date("2025-03-05T01:30:00Z"), "2025-03-04");
+        assert_eq!(date("2025-03-05T03:00:00Z"), "2025-03-05");
+        assert_eq!(date("2025-03-05T22:59:00Z"), "2025-03-05");
+        assert_eq!(date("2025-03-05T23:00:00Z"), "2025-03-05");
+        let midnight = Calendar {
+            rollover_hour: 0,
+            ..calendar
+        };
+        assert_eq!(
+            midnight
+                .date_of("2025-03-05T23:00:00Z".parse().unwrap())
+                .to_string(),
+            "2025-03-06"
+        );
+    }
+
+    fn with_work_profile() -> Config {
+        let mut config = Config {
+            db_path: "/data/db.json".into(),
+            sync: SyncConfig {
+                server: Some("home:7465".to_string()),
+                ..SyncConfig::default()
+            },
+            ..Config::default()
+        };
+        config.profiles.insert(
+            "work".to_string(),
+            Profile {
+                db_path: "/data/work.json".into(),
+                sync_server: None,
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn picks_profile_database() {
+        let mut config = with_work_profile();
+        assert_eq!(config.database(), Path::new("/data/db.json"));
+        assert_eq!(config.sync_server().as_deref(), Some("home:7465"));
+        config.profile = Some("work".to_string());
+        assert_eq!(config.database(), Path::new("/data/work.json"));
+        // Profiles never fall back to the default profile's server
+        assert_eq!(config.sync_server(), None);
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let config = Config {
+            profile: Some("work".to_string()),
+            ..with_work_profile()
+        };
+        assert_eq!(ConfigOverrides::default().apply(config.clone()), config);
+
+        let overrides = ConfigOverrides {
+            db_path: None,
+            profile: Some(None),
+        };
+        assert_eq!(
+            overrides.apply(config.clone()).database(),
+            Path::new("/data/db.json")
+        );
+        // An explicit database leaves out whichever profile is picked
+        let overrides = ConfigOverrides {
+            db_path: Some("/tmp/other.json".into()),
+            profile: Some(Some("work".to_string())),
+        };
+        let applied = overrides.apply(config);
+        assert_eq!(applied.profile, None);
+        assert_eq!(applied.database(), Path::new("/tmp/other.json"));
+    }
+
+    #[cfg(not(feature = "web"))]
+    #[test]
+    fn saves_profile() {
+        let path = std::env::temp_dir()
+            .join(format!("ritual-config-{}", uuid::Uuid::new_v4()))
+            .join("config.toml");
+        with_work_profile().save(&path).unwrap();
+        Config::save_profile(&path, Some("work".to_string())).unwrap();
+        assert_eq!(
+            Config::load(&path).unwrap().profile.as_deref(),
+            Some("work")
+        );
+        assert!(Config::save_profile(&path, Some("home".to_string())).is_err());
+        Config::save_profile(&path, None).unwrap();
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.profile, None);
+        assert_eq!(config.profiles.len(), 1);
+    }
+
+    #[cfg(not(feature = "web"))]
+    #[test]
+    fn validates_profile_names() {
+        let profile = Profile::new("work").unwrap();
+        assert!(profile.db_path.ends_with("profiles/work.json"));
+        for name in ["", ".", "..", "../x", "a/b", "a\\b", "a\nb"] {
+            assert!(Profile::new(name).is_err(), "{name:?}");
+        }
+    }
+}
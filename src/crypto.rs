@@ -0,0 +1,300 @@
+//! Encryption at rest with a key derived from a passphrase.
+//!
+//! Encrypted files hold a JSON envelope with the Argon2id salt the key was derived
+//! with, a random nonce and the XChaCha20-Poly1305 ciphertext of the plain file.
+//! The salt stays the same until the passphrase changes, so the slow key derivation
+//! only happens when unlocking, while every write gets a fresh nonce.
+//!
+//! Sync payloads are sealed with other keys, derived from the same passphrase with
+//! a random salt of each replica's own. The salt travels in the envelope, so devices
+//! sharing a passphrase can read each other's operations without ever sharing their
+//! files, and a sync server can't attack every user's passphrase at once.
+
+use argon2::Argon2;
+use base64::prelude::*;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+#[cfg(not(feature = "web"))]
+use std::collections::HashMap;
+
+use crate::prelude::*;
+
+pub const SALT_LEN: usize = 16;
+const FORMAT_VERSION: u32 = 1;
+
+/// A passphrase, kept out of logs
+#[derive(Clone, PartialEq)]
+pub struct Passphrase(String);
+
+impl Passphrase {
+    pub fn new(passphrase: String) -> Self {
+        Self(passphrase)
+    }
+}
+
+impl std::fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Passphrase(..)")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    ritual_encrypted: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl Envelope {
+    fn parse(contents: &str) -> Option<Self> {
+        serde_json::from_str(contents).ok()
+    }
+
+    fn salt(&self) -> Result<[u8; SALT_LEN]> {
+        BASE64_STANDARD
+            .decode(&self.salt)?
+            .try_into()
+            .map_err(|_| eyre::eyre!("Invalid salt in encrypted file"))
+    }
+}
+
+pub fn is_encrypted(contents: &str) -> bool {
+    Envelope::parse(contents).is_some()
+}
+
+/// A key derived from a passphrase
+#[derive(Clone)]
+pub struct Cipher {
+    cipher: XChaCha20Poly1305,
+    salt: [u8; SALT_LEN],
+    /// Kept to derive the keys of sync payloads, whose salts aren't known up front
+    #[cfg(not(feature = "web"))]
+    passphrase: Passphrase,
+}
+
+#[cfg(not(feature = "web"))]
+pub fn random_salt() -> [u8; SALT_LEN] {
+    use chacha20poly1305::aead::rand_core::RngCore;
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+fn derive_key(passphrase: &Passphrase, salt: &[u8; SALT_LEN]) -> Result<XChaCha20Poly1305> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.0.as_bytes(), salt, &mut key)
+        .map_err(|err| eyre::eyre!("Failed to derive key: {}", err))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+fn encrypt_with(
+    cipher: &XChaCha20Poly1305,
+    salt: [u8; SALT_LEN],
+    plaintext: &str,
+) -> Result<String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|err| eyre::eyre!("Failed to encrypt: {}", err))?;
+    Ok(serde_json::to_string(&Envelope {
+        ritual_encrypted: FORMAT_VERSION,
+        salt: BASE64_STANDARD.encode(salt),
+        nonce: BASE64_STANDARD.encode(nonce),
+        ciphertext: BASE64_STANDARD.encode(ciphertext),
+    })?)
+}
+
+fn decrypt_with(
+    cipher: &XChaCha20Poly1305,
+    salt: [u8; SALT_LEN],
+    contents: &str,
+) -> Result<String> {
+    let envelope = Envelope::parse(contents).ok_or_else(|| eyre::eyre!("File is not encrypted"))?;
+    if envelope.ritual_encrypted != FORMAT_VERSION {
+        return Err(eyre::eyre!(
+            "Unsupported encryption format {}",
+            envelope.ritual_encrypted
+        ));
+    }
+    if envelope.salt()? != salt {
+        return Err(eyre::eyre!(
+            "File was encrypted with a different passphrase"
+        ));
+    }
+    let nonce = BASE64_STANDARD.decode(&envelope.nonce)?;
+    if nonce.len() != 24 {
+        return Err(eyre::eyre!("Invalid nonce in encrypted file"));
+    }
+    let plaintext = cipher
+        .decrypt(
+            XNonce::from_slice(&nonce),
+            BASE64_STANDARD.decode(&envelope.ciphertext)?.as_slice(),
+        )
+        .map_err(|_| eyre::eyre!("Wrong passphrase or corrupted file"))?;
+    Ok(String::from_utf8(plaintext)?)
+}
+
+impl std::fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cipher(..)")
+    }
+}
+
+impl Cipher {
+    fn derive(passphrase: &Passphrase, salt: [u8; SALT_LEN]) -> Result<Self> {
+        Ok(Self {
+            cipher: derive_key(passphrase, &salt)?,
+            salt,
+            #[cfg(not(feature = "web"))]
+            passphrase: passphrase.clone(),
+        })
+    }
+
+    /// A new key for `passphrase`, with a fresh salt
+    #[cfg(not(feature = "web"))]
+    pub fn new(passphrase: &Passphrase) -> Result<Self> {
+        Self::derive(passphrase, random_salt())
+    }
+
+    /// The key `contents` were encrypted with, checked by decrypting them
+    pub fn unlock(passphrase: &Passphrase, contents: &str) -> Result<Self> {
+        let envelope =
+            Envelope::parse(contents).ok_or_else(|| eyre::eyre!("File is not encrypted"))?;
+        let cipher = Self::derive(passphrase, envelope.salt()?)?;
+        cipher.decrypt(contents)?;
+        Ok(cipher)
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        encrypt_with(&self.cipher, self.salt, plaintext)
+    }
+
+    pub fn decrypt(&self, contents: &str) -> Result<String> {
+        decrypt_with(&self.cipher, self.salt, contents)
+    }
+
+    /// The keys of sync payloads, sealing them with `salt`
+    #[cfg(not(feature = "web"))]
+    pub fn sync_keys(&self, salt: [u8; SALT_LEN]) -> SyncKeys {
+        SyncKeys {
+            passphrase: self.passphrase.clone(),
+            salt,
+            keys: HashMap::new(),
+        }
+    }
+}
+
+/// Keys of sync payloads, derived once for each salt they come with
+#[cfg(not(feature = "web"))]
+pub struct SyncKeys {
+    passphrase: Passphrase,
+    salt: [u8; SALT_LEN],
+    keys: HashMap<[u8; SALT_LEN], XChaCha20Poly1305>,
+}
+
+#[cfg(not(feature = "web"))]
+impl SyncKeys {
+    fn key(&mut self, salt: [u8; SALT_LEN]) -> Result<&XChaCha20Poly1305> {
+        if !self.keys.contains_key(&salt) {
+            let key = derive_key(&self.passphrase, &salt)?;
+            self.keys.insert(salt, key);
+        }
+        Ok(&self.keys[&salt])
+    }
+
+    /// Encrypts a sync payload so any device with the same passphrase can read it
+    pub fn seal(&mut self, plaintext: &str) -> Result<String> {
+        let salt = self.salt;
+        encrypt_with(self.key(salt)?, salt, plaintext)
+    }
+
+    pub fn open(&mut self, sealed: &str) -> Result<String> {
+        let salt = Envelope::parse(sealed)
+            .ok_or_else(|| eyre::eyre!("Payload is not encrypted"))?
+            .salt()?;
+        decrypt_with(self.key(salt)?, salt, sealed)
+    }
+}
+
+#[cfg(not(feature = "web"))]
+impl std::fmt::Debug for SyncKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SyncKeys(..)")
+    }
+}
+
+/// Contents of a file as written to disk, encrypted if there is a key
+pub fn encode(plaintext: String, cipher: Option<&Cipher>) -> Result<String> {
+    match cipher {
+        Some(cipher) => cipher.encrypt(&plaintext),
+        None => Ok(plaintext),
+    }
+}
+
+/// Plain contents of a file, which may or may not be encrypted
+pub fn decode(contents: &str, cipher: Option<&Cipher>) -> Result<String> {
+    if !is_encrypted(contents) {
+        return Ok(contents.to_string());
+    }
+    match cipher {
+        Some(cipher) => cipher.decrypt(contents),
+        None => {
+            trace!("Refusing to read an encrypted file without a key");
+            Err(eyre::eyre!("File is encrypted, unlock it first"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let passphrase = Passphrase::new("correct horse".to_string());
+        let cipher = Cipher::derive(&passphrase, [7; SALT_LEN]).unwrap();
+        let contents = cipher.encrypt("{\"days\":{}}").unwrap();
+        assert!(is_encrypted(&contents));
+        assert!(!is_encrypted("{\"days\":{},\"habits\":{}}"));
+
+        let unlocked = Cipher::unlock(&passphrase, &contents).unwrap();
+        assert_eq!(unlocked.decrypt(&contents).unwrap(), "{\"days\":{}}");
+        let wrong = Passphrase::new("battery staple".to_string());
+        assert!(Cipher::unlock(&wrong, &contents).is_err());
+    }
+
+    #[cfg(not(feature = "web"))]
+    #[test]
+    fn sync_keys_are_shared() {
+        let passphrase = Passphrase::new("correct horse".to_string());
+        let (a, b) = (
+            Cipher::derive(&passphrase, [1; SALT_LEN]).unwrap(),
+            Cipher::derive(&passphrase, [2; SALT_LEN]).unwrap(),
+        );
+        let (mut a_keys, mut b_keys) = (a.sync_keys([3; SALT_LEN]), b.sync_keys([4; SALT_LEN]));
+        // Each device's files stay its own, but sync payloads go both ways
+        assert!(b.decrypt(&a.encrypt("file").unwrap()).is_err());
+        let sealed = a_keys.seal("op").unwrap();
+        assert_eq!(b_keys.open(&sealed).unwrap(), "op");
+        assert_eq!(a_keys.open(&b_keys.seal("op").unwrap()).unwrap(), "op");
+        // Each replica's payloads have a salt of their own
+        assert_ne!(
+            Envelope::parse(&sealed).unwrap().salt().unwrap(),
+            [4; SALT_LEN]
+        );
+        let other = Cipher::derive(
+            &Passphrase::new("battery staple".to_string()),
+            [1; SALT_LEN],
+        );
+        assert!(other
+            .unwrap()
+            .sync_keys([3; SALT_LEN])
+            .open(&sealed)
+            .is_err());
+    }
+}
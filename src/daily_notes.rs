@@ -0,0 +1,201 @@
+//! Export into the daily notes of a Markdown vault, like Obsidian's or Logseq's.
+//!
+//! Each day is written to `YYYY-MM-DD.md` in the configured folder, inside a block
+//! between two HTML comments. Exporting again only replaces that block, so
+//! whatever else is written in the note stays as it is.
+
+use chrono::NaiveDate;
+use color_eyre::eyre::{self, WrapErr};
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::config::{Calendar, DailyNotesConfig};
+use crate::db::State;
+use crate::prelude::*;
+use crate::types::{Day, HabitStatus};
+
+const BLOCK_START: &str = "<!-- ritual:start -->";
+const BLOCK_END: &str = "<!-- ritual:end -->";
+
+/// Writes the days from `from` to `to` into their notes, returning how many notes
+/// changed
+pub fn export(
+    state: &State,
+    calendar: &Calendar,
+    config: &DailyNotesConfig,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<usize> {
+    let folder = config
+        .folder
+        .as_ref()
+        .ok_or_else(|| eyre::eyre!("Set `daily_notes.folder` in the config or pass --folder"))?;
+    std::fs::create_dir_all(folder)
+        .wrap_err_with(|| format!("Failed to create {}", folder.display()))?;
+    let mut changed = 0;
+    for day in state.days_in(from..=to, calendar) {
+        let date = calendar.date_of(day.date);
+        let path = folder.join(format!("{}.md", date.format("%Y-%m-%d")));
+        let note = read_note(&path)?;
+        let updated = update(&note, &render(&config.template, state, day, date));
+        if updated != note {
+            info!("Writing daily note {}", path.display());
+            std::fs::write(&path, updated)
+                .wrap_err_with(|| format!("Failed to write {}", path.display()))?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+fn read_note(path: &Path) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(note) => Ok(note),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err).wrap_err_with(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// The block for a day, `template` filled in and wrapped in the block markers
+pub fn render(template: &str, state: &State, day: &Day, date: NaiveDate) -> String {
+    let habits = day
+        .habits
+        .values()
+        .map(|habit_ref| {
+            let check = if habit_ref.status == HabitStatus::Done {
+                'x'
+            } else {
+                ' '
+            };
+            let name = state.habit_name(habit_ref);
+            match &habit_ref.note {
+                Some(note) => format!("- [{check}] {name} — {note}"),
+                None => format!("- [{check}] {name}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let value = |placeholder: &str| match placeholder {
+        "date" => Some(date.format("%Y-%m-%d").to_string()),
+        "habits" => Some(habits.clone()),
+        "mood" => Some(day.mood.map(|mood| mood.to_string()).unwrap_or_default()),
+        "journal" => Some(day.journal.clone().unwrap_or_default()),
+        _ => None,
+    };
+    // One pass, so placeholders inside the filled in text are left as they are
+    let mut body = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        body.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let filled = rest
+            .find('}')
+            .and_then(|end| Some((end, value(&rest[1..end])?)));
+        match filled {
+            Some((end, value)) => {
+                body.push_str(&value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                body.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    body.push_str(rest);
+    // A marker in a journal would end the block early. Escaped, it still reads the same
+    let body = body
+        .replace(BLOCK_START, &BLOCK_START.replacen('<', "&lt;", 1))
+        .replace(BLOCK_END, &BLOCK_END.replacen('<', "&lt;", 1));
+    format!("{BLOCK_START}\n{}\n{BLOCK_END}", body.trim_end())
+}
+
+/// Puts `block` in place of the note's existing block, or at its end if it has
+/// none yet
+pub fn update(note: &str, block: &str) -> String {
+    let existing = note.find(BLOCK_START).and_then(|start| {
+        let end = start + note[start..].find(BLOCK_END)? + BLOCK_END.len();
+        Some((start, end))
+    });
+    match existing {
+        Some((start, end)) => format!("{}{}{}", &note[..start], block, &note[end..]),
+        None if note.trim().is_empty() => format!("{block}\n"),
+        None => format!("{}\n\n{block}\n", note.trim_end()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{NonEmpty, Validate};
+
+    fn day() -> (State, Day, NaiveDate) {
+        let calendar = Calendar {
+            timezone: Some(chrono_tz::UTC),
+            rollover_hour: 0,
+        };
+        let now = "2025-03-05T12:00:00Z".parse().unwrap();
+        let mut state = State::default();
+        state.add_day(now, &calendar).unwrap();
+        let day_id = *state.days.keys().next().unwrap();
+        for title in ["Run", "Read"] {
+            let title = NonEmpty::new_validated(title.to_string()).unwrap();
+            state.add_habit_to_day(title, day_id).unwrap();
+        }
+        let run = *state.habits.keys().next().unwrap();
+        state
+            .set_habit_status(day_id, run, HabitStatus::Done)
+            .unwrap();
+        state.set_habit_note(day_id, run, "5K".to_string()).unwrap();
+        state.set_day_mood(day_id, Some(4)).unwrap();
+        let day = state.days[&day_id].clone();
+        (state, day, calendar.date_of(now))
+    }
+
+    #[test]
+    fn renders_template() {
+        let (state, day, date) = day();
+        assert_eq!(
+            render("# {date}\nMood: {mood}\n\n{habits}\n{journal}\n", &state, &day, date),
+            "<!-- ritual:start -->\n# 2025-03-05\nMood: 4\n\n- [x] Run — 5K\n- [ ] Read\n<!-- ritual:end -->"
+        );
+    }
+
+    #[test]
+    fn leaves_placeholders_in_values_alone() {
+        let (mut state, mut day, date) = day();
+        let run = *state.habits.keys().next().unwrap();
+        let title = NonEmpty::new_validated("{journal}".to_string()).unwrap();
+        state.rename_habit(run, title).unwrap();
+        day.journal = Some("Felt {mood}, see {date}".to_string());
+        assert_eq!(
+            render("{journal} {unknown} {mood\n{habits}", &state, &day, date),
+            "<!-- ritual:start -->\nFelt {mood}, see {date} {unknown} {mood\n- [x] {journal} — 5K\n- [ ] Read\n<!-- ritual:end -->"
+        );
+    }
+
+    #[test]
+    fn updates_only_the_block() {
+        let block = "<!-- ritual:start -->\nnew\n<!-- ritual:end -->";
+        assert_eq!(update("", block), format!("{block}\n"));
+        assert_eq!(
+            update("# Wednesday\nWent out\n", block),
+            format!("# Wednesday\nWent out\n\n{block}\n")
+        );
+        let note = "# Wednesday\n<!-- ritual:start -->\nold\n<!-- ritual:end -->\nWent out\n";
+        let updated = update(note, block);
+        assert_eq!(updated, format!("# Wednesday\n{block}\nWent out\n"));
+        assert_eq!(update(&updated, block), updated);
+
+        // Markers written in the journal don't end the block
+        let (state, mut day, date) = day();
+        day.journal = Some("Notes end with <!-- ritual:end --> here".to_string());
+        let block = render("{journal}", &state, &day, date);
+        assert_eq!(block.matches(BLOCK_END).count(), 1);
+        let exported = update("# Wednesday\n", &block);
+        assert_eq!(update(&exported, &block), exported);
+        day.journal = Some("Rewritten".to_string());
+        let reexported = update(&exported, &render("{journal}", &state, &day, date));
+        assert!(!reexported.contains("here"));
+    }
+}
@@ -1,71 +1,356 @@
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
 use color_eyre::eyre;
+use indexmap::IndexMap;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::config::Calendar;
+use crate::crypto::{self, Cipher, Passphrase};
 use crate::prelude::*;
-use crate::types::{DateTime, Day, Habit, HabitRef};
-use crate::utils::{HashmapExt, NonEmpty, Validate};
-use std::collections::HashMap;
+use crate::storage;
+use crate::types::{DateTime, Day, Habit, HabitRef, HabitStatus, Pause, Schedule};
+use crate::utils::{HashmapExt, HexColor, NonEmpty, Validate};
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Bound;
 use std::path::PathBuf;
+use std::sync::Mutex;
 
 #[derive(Debug)]
 pub struct Db {
     path: PathBuf,
+    /// Hash of the contents last loaded or saved, to tell our own writes apart
+    /// from changes made by someone else
+    synced: Mutex<u64>,
+    /// Key of an encrypted database, once it is unlocked
+    cipher: Option<Cipher>,
+}
+
+fn hash_contents(contents: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct State {
-    pub days: HashMap<Uuid, Day>,
-    pub habits: HashMap<Uuid, Habit>,
+    pub days: Days,
+    pub habits: IndexMap<Uuid, Habit>,
+    #[serde(default)]
+    pub pauses: IndexMap<Uuid, Pause>,
 }
 
 impl Db {
     #[tracing::instrument]
     pub fn open_or_new(path: PathBuf) -> Result<Self> {
         info!("Opening database at {:?}", path);
-        if !path.exists() {
+        if !storage::exists(&path) {
             info!("Database does not exist, creating a new one");
-            // Create dir
-            std::fs::create_dir_all(
-                path.parent()
-                    .expect("Invalid path, failed to get parent directory"),
-            )?;
             let empty = State::default();
-            let db = std::fs::File::create(&path)?;
-            let writer = std::io::BufWriter::new(db);
-            serde_json::to_writer(writer, &empty)?;
+            storage::write(&path, &serde_json::to_string(&empty)?)?;
+        }
+        Self::open(path)
+    }
+
+    /// Opens a database that must already exist
+    #[tracing::instrument]
+    pub fn open(path: PathBuf) -> Result<Self> {
+        if !storage::exists(&path) {
+            return Err(eyre::eyre!("No database at {:?}", path));
         }
+        Ok(Self {
+            path,
+            synced: Mutex::new(0),
+            cipher: None,
+        })
+    }
+
+    /// Whether the file on disk is encrypted, whether or not it is unlocked
+    pub fn is_encrypted(&self) -> Result<bool> {
+        Ok(crypto::is_encrypted(&storage::read_to_string(&self.path)?))
+    }
+
+    pub fn is_locked(&self) -> Result<bool> {
+        Ok(self.cipher.is_none() && self.is_encrypted()?)
+    }
+
+    pub fn cipher(&self) -> Option<&Cipher> {
+        self.cipher.as_ref()
+    }
+
+    /// Derives the key of an encrypted database, failing if the passphrase is wrong
+    #[tracing::instrument(skip(passphrase))]
+    pub fn unlock(&mut self, passphrase: &Passphrase) -> Result<()> {
+        info!("Unlocking database {:?}", self.path);
+        let contents = storage::read_to_string(&self.path)?;
+        self.cipher = Some(Cipher::unlock(passphrase, &contents)?);
+        Ok(())
+    }
+
+    /// Encrypts the database with a new passphrase from the next save on, or stores
+    /// it in plain text if there is none. Only the CLI changes passphrases.
+    #[cfg(not(feature = "web"))]
+    #[tracing::instrument(skip(passphrase))]
+    pub fn set_passphrase(&mut self, passphrase: Option<&Passphrase>) -> Result<()> {
+        self.cipher = passphrase.map(Cipher::new).transpose()?;
+        Ok(())
+    }
+
+    /// Parses the contents of a database file, decrypting them if needed
+    pub fn parse(&self, contents: &str) -> Result<State> {
+        Ok(serde_json::from_str(&crypto::decode(
+            contents,
+            self.cipher(),
+        )?)?)
+    }
 
-        Ok(Self { path })
+    fn set_synced(&self, contents: &str) {
+        *self.synced.lock().unwrap_or_else(|e| e.into_inner()) = hash_contents(contents);
     }
 
     #[tracing::instrument]
     pub fn save(&self, state: &State) -> Result<()> {
         info!("Saving database to {:?}", self.path);
-        let db = std::fs::File::create(&self.path)?;
-        let writer = std::io::BufWriter::new(db);
-        serde_json::to_writer(writer, state)?;
+        let contents = crypto::encode(serde_json::to_string(state)?, self.cipher())?;
+        storage::write(&self.path, &contents)?;
+        self.set_synced(&contents);
         Ok(())
     }
 
     #[tracing::instrument]
     pub fn load(&self) -> Result<State> {
         info!("Loading database from {:?}", self.path);
-        let db = std::fs::File::open(&self.path)?;
-        let reader = std::io::BufReader::new(db);
-        let state: State = serde_json::from_reader(reader)?;
+        let contents = storage::read_to_string(&self.path)?;
+        let state = self.parse(&contents)?;
+        self.set_synced(&contents);
         Ok(state)
     }
+
+    /// Loads the database if the file no longer holds what was last loaded or
+    /// saved, meaning it was changed by another program
+    #[tracing::instrument]
+    pub fn load_if_changed(&self) -> Result<Option<State>> {
+        let contents = storage::read_to_string(&self.path)?;
+        if hash_contents(&contents) == *self.synced.lock().unwrap_or_else(|e| e.into_inner()) {
+            trace!("Database {:?} is unchanged", self.path);
+            return Ok(None);
+        }
+        info!("Database {:?} was changed externally", self.path);
+        let state = self.parse(&contents)?;
+        self.set_synced(&contents);
+        Ok(Some(state))
+    }
+}
+
+/// The days of a [`State`] by id, which also keeps them in order of when they
+/// start, so a range of dates is found without going through every day. Reads go
+/// through the map; anything that changes which days there are or their dates
+/// goes through `Days` itself, so the order stays up to date.
+#[derive(Debug, Clone, Default)]
+pub struct Days {
+    days: HashMap<Uuid, Day>,
+    by_time: BTreeSet<(DateTime, Uuid)>,
+}
+
+impl Days {
+    pub fn insert(&mut self, id: Uuid, day: Day) -> Option<Day> {
+        self.by_time.insert((day.date, id));
+        let old = self.days.insert(id, day)?;
+        if old.date != self.days[&id].date {
+            self.by_time.remove(&(old.date, id));
+        }
+        Some(old)
+    }
+
+    /// Moves the day with `id` to `date`, adding it if there is none
+    pub fn set_date(&mut self, id: Uuid, date: DateTime) {
+        match self.days.get_mut(&id) {
+            Some(day) => {
+                self.by_time.remove(&(day.date, id));
+                self.by_time.insert((date, id));
+                day.date = date;
+            }
+            None => {
+                self.insert(
+                    id,
+                    Day {
+                        id,
+                        ..Day::new(date)
+                    },
+                );
+            }
+        }
+    }
+
+    /// The day with `id`, to edit anything but its date
+    pub fn get_mut(&mut self, id: &Uuid) -> Option<&mut Day> {
+        self.days.get_mut(id)
+    }
+
+    /// Removes every day and its date index
+    #[cfg(test)]
+    pub fn clear(&mut self) {
+        self.days.clear();
+        self.by_time.clear();
+    }
+}
+
+impl std::ops::Deref for Days {
+    type Target = HashMap<Uuid, Day>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.days
+    }
+}
+
+impl From<HashMap<Uuid, Day>> for Days {
+    fn from(days: HashMap<Uuid, Day>) -> Self {
+        let by_time = days.values().map(|day| (day.date, day.id)).collect();
+        Self { days, by_time }
+    }
+}
+
+impl Serialize for Days {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        self.days.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Days {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        HashMap::deserialize(deserializer).map(Self::from)
+    }
 }
 
 impl State {
-    pub fn add_day(&mut self, date: DateTime) -> Result<()> {
-        let day = Day::new(date);
+    /// The day that falls on `date`, if it has been added
+    pub fn day_on(&self, date: NaiveDate, calendar: &Calendar) -> Option<&Day> {
+        self.days_in(date..=date, calendar).into_iter().next()
+    }
+
+    /// The days that fall within `dates`, oldest first
+    pub fn days_in(
+        &self,
+        dates: impl std::ops::RangeBounds<NaiveDate>,
+        calendar: &Calendar,
+    ) -> Vec<&Day> {
+        // Days start at different times depending on the timezone and rollover
+        // hour, so look a couple of days further out and check their dates
+        let around = |bound: Bound<&NaiveDate>, days: i64, id: Uuid| {
+            bound
+                .map(|date| {
+                    (*date + TimeDelta::days(days))
+                        .and_time(NaiveTime::MIN)
+                        .and_utc()
+                })
+                .map(|time| (time, id))
+        };
+        let range = (
+            around(dates.start_bound(), -2, Uuid::nil()),
+            around(dates.end_bound(), 2, Uuid::max()),
+        );
+        self.days
+            .by_time
+            .range(range)
+            .map(|(_, id)| &self.days[id])
+            .map(|day| (calendar.date_of(day.date), day))
+            .filter(|(date, _)| dates.contains(date))
+            .sorted_by_key(|(date, _)| *date)
+            .map(|(_, day)| day)
+            .collect()
+    }
+
+    /// The name to show for a check-in, the habit's current title when it still exists
+    pub fn habit_name<'a>(&'a self, habit_ref: &'a HabitRef) -> &'a str {
+        self.habits
+            .get(&habit_ref.id)
+            .map_or(&habit_ref.name, |habit| &habit.title)
+    }
+
+    /// The date of the oldest of the last `count` days before `before`, `None` if
+    /// there are none
+    pub fn page_start(
+        &self,
+        before: NaiveDate,
+        count: usize,
+        calendar: &Calendar,
+    ) -> Option<NaiveDate> {
+        let older = self.days_in(..before, calendar);
+        older
+            .get(older.len().saturating_sub(count))
+            .map(|day| calendar.date_of(day.date))
+    }
+
+    pub fn is_paused(&self, habit_id: Uuid, date: NaiveDate) -> bool {
+        self.pauses
+            .values()
+            .any(|pause| pause.covers(habit_id, date))
+    }
+
+    /// The habits a day on `date` starts out with
+    fn habit_refs_on(&self, date: NaiveDate) -> Vec<HabitRef> {
+        self.habits
+            .values()
+            .filter(|h| !h.archived && h.schedule.includes(date) && !self.is_paused(h.id, date))
+            .map(|habit| HabitRef {
+                id: habit.id,
+                name: habit.title.clone(),
+                status: HabitStatus::Pending,
+                note: None,
+            })
+            .collect()
+    }
+
+    pub fn add_day(&mut self, date: DateTime, calendar: &Calendar) -> Result<()> {
+        let mut day = Day::new(date);
         let day_id = day.id;
         if self.days.contains_key(&day_id) {
             return Err(eyre::eyre!("Day with id {} already exists", day.id));
         }
-        self.days.fallible_insert(day_id, day)?;
+        for habit_ref in self.habit_refs_on(calendar.date_of(date)) {
+            day.habits.fallible_insert(habit_ref.id, habit_ref)?;
+        }
+        self.days.insert(day_id, day);
+        Ok(())
+    }
+
+    pub fn add_pause(&mut self, pause: Pause) -> Result<()> {
+        if pause.end < pause.start {
+            return Err(eyre::eyre!("Pause ends before it starts"));
+        }
+        if let Some(habit_id) = pause.habit_id {
+            if !self.habits.contains_key(&habit_id) {
+                return Err(eyre::eyre!("Habit with ID {} not found", habit_id));
+            }
+        }
+        self.pauses.fallible_insert(pause.id, pause)?;
+        Ok(())
+    }
+
+    /// Ends a pause as of today, bringing its habits back into today's day if it
+    /// was added while they were paused. Pauses are never removed, so syncing can
+    /// tell an ended pause from a missing one; one that starts today or later is
+    /// left covering no days.
+    pub fn end_pause(&mut self, pause_id: Uuid, calendar: &Calendar) -> Result<()> {
+        let today = calendar.today();
+        let pause = self
+            .pauses
+            .get_mut(&pause_id)
+            .ok_or_else(|| eyre::eyre!("Pause with ID {} not found", pause_id))?;
+        pause.end = pause.end.min(today.pred_opt().unwrap_or(today));
+        let habit_refs = self.habit_refs_on(today);
+        let today_id = self.day_on(today, calendar).map(|day| day.id);
+        if let Some(day) = today_id.and_then(|id| self.days.get_mut(&id)) {
+            for habit_ref in habit_refs {
+                day.habits.entry(habit_ref.id).or_insert(habit_ref);
+            }
+        }
         Ok(())
     }
 
@@ -77,6 +362,10 @@ impl State {
         let habit = Habit {
             id: Uuid::new_v4(),
             title: title.inner(),
+            schedule: Schedule::default(),
+            reminder: None,
+            color: None,
+            archived: false,
         };
         if self.habits.contains_key(&habit.id) {
             return Err(eyre::eyre!("Habit with id {} already exists", habit.id));
@@ -86,14 +375,20 @@ impl State {
             HabitRef {
                 id: habit.id,
                 name: habit.title.clone(),
-                done: false,
+                status: HabitStatus::Pending,
+                note: None,
             },
         )?;
         self.habits.fallible_insert(habit.id, habit)?;
         Ok(())
     }
 
-    pub fn set_habit_done(&mut self, day_id: Uuid, habit_id: Uuid, done: bool) -> Result<()> {
+    pub fn set_habit_status(
+        &mut self,
+        day_id: Uuid,
+        habit_id: Uuid,
+        status: HabitStatus,
+    ) -> Result<()> {
         let day = self
             .days
             .get_mut(&day_id)
@@ -103,7 +398,262 @@ impl State {
             .get_mut(&habit_id)
             .ok_or_else(|| eyre::eyre!("Habit with ID {} not found in day {}", habit_id, day_id))?;
 
-        habit_ref.done = done;
+        habit_ref.status = status;
         Ok(())
     }
+
+    /// Sets the note of a habit on a day, clearing it if `note` is blank
+    pub fn set_habit_note(&mut self, day_id: Uuid, habit_id: Uuid, note: String) -> Result<()> {
+        let day = self
+            .days
+            .get_mut(&day_id)
+            .ok_or_else(|| eyre::eyre!("Day not found"))?;
+        let habit_ref = day
+            .habits
+            .get_mut(&habit_id)
+            .ok_or_else(|| eyre::eyre!("Habit with ID {} not found in day {}", habit_id, day_id))?;
+        habit_ref.note = (!note.trim().is_empty()).then_some(note);
+        Ok(())
+    }
+
+    /// Sets the journal entry of a day, clearing it if `journal` is blank
+    pub fn set_day_journal(&mut self, day_id: Uuid, journal: String) -> Result<()> {
+        let day = self
+            .days
+            .get_mut(&day_id)
+            .ok_or_else(|| eyre::eyre!("Day not found"))?;
+        day.journal = (!journal.trim().is_empty()).then_some(journal);
+        Ok(())
+    }
+
+    pub fn set_day_mood(&mut self, day_id: Uuid, mood: Option<u8>) -> Result<()> {
+        if mood.is_some_and(|mood| !Day::MOODS.contains(&mood)) {
+            return Err(eyre::eyre!("Mood must be between 1 and 5"));
+        }
+        let day = self
+            .days
+            .get_mut(&day_id)
+            .ok_or_else(|| eyre::eyre!("Day not found"))?;
+        day.mood = mood;
+        Ok(())
+    }
+
+    pub fn set_habit_reminder(
+        &mut self,
+        habit_id: Uuid,
+        reminder: Option<NaiveTime>,
+    ) -> Result<()> {
+        let habit = self
+            .habits
+            .get_mut(&habit_id)
+            .ok_or_else(|| eyre::eyre!("Habit with ID {} not found", habit_id))?;
+        habit.reminder = reminder;
+        Ok(())
+    }
+
+    pub fn set_habit_schedule(&mut self, habit_id: Uuid, schedule: Schedule) -> Result<()> {
+        let habit = self
+            .habits
+            .get_mut(&habit_id)
+            .ok_or_else(|| eyre::eyre!("Habit with ID {} not found", habit_id))?;
+        habit.schedule = schedule;
+        Ok(())
+    }
+
+    /// Renames a habit. Past check-ins keep their stored name and show the new title
+    /// through [`State::habit_name`], so a rename syncs as a single habit change
+    pub fn rename_habit(&mut self, habit_id: Uuid, title: NonEmpty<String>) -> Result<()> {
+        let habit = self
+            .habits
+            .get_mut(&habit_id)
+            .ok_or_else(|| eyre::eyre!("Habit with ID {} not found", habit_id))?;
+        habit.title = title.inner();
+        Ok(())
+    }
+
+    pub fn set_habit_archived(&mut self, habit_id: Uuid, archived: bool) -> Result<()> {
+        let habit = self
+            .habits
+            .get_mut(&habit_id)
+            .ok_or_else(|| eyre::eyre!("Habit with ID {} not found", habit_id))?;
+        habit.archived = archived;
+        Ok(())
+    }
+
+    pub fn set_habit_color(&mut self, habit_id: Uuid, color: Option<HexColor>) -> Result<()> {
+        let habit = self
+            .habits
+            .get_mut(&habit_id)
+            .ok_or_else(|| eyre::eyre!("Habit with ID {} not found", habit_id))?;
+        habit.color = color.map(|c| c.inner());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALENDAR: Calendar = Calendar {
+        timezone: Some(chrono_tz::UTC),
+        rollover_hour: 0,
+    };
+
+    /// Yesterday with `Run` and `Read` on it, and `Run` paused from today on
+    fn paused_run() -> (State, Uuid, Uuid) {
+        let mut state = State::default();
+        let now = chrono::Utc::now();
+        state
+            .add_day(now - chrono::TimeDelta::days(1), &CALENDAR)
+            .unwrap();
+        let day_id = *state.days.keys().next().unwrap();
+        for title in ["Run", "Read"] {
+            let title = NonEmpty::new_validated(title.to_string()).unwrap();
+            state.add_habit_to_day(title, day_id).unwrap();
+        }
+        let run = *state.habits.keys().next().unwrap();
+        let today = CALENDAR.date_of(now);
+        let pause = Pause::new(today, today + chrono::TimeDelta::days(7), Some(run));
+        let pause_id = pause.id;
+        state.add_pause(pause).unwrap();
+        (state, run, pause_id)
+    }
+
+    #[test]
+    fn new_days_leave_paused_habits_out() {
+        let (mut state, run, _) = paused_run();
+        state.add_day(chrono::Utc::now(), &CALENDAR).unwrap();
+        let today = state.day_on(CALENDAR.today(), &CALENDAR).unwrap();
+        assert_eq!(today.habits.len(), 1);
+        assert!(!today.habits.contains_key(&run));
+        assert!(state.is_paused(run, CALENDAR.today()));
+    }
+
+    #[test]
+    fn ending_pause_brings_habits_back() {
+        let (mut state, run, pause_id) = paused_run();
+        state.add_day(chrono::Utc::now(), &CALENDAR).unwrap();
+        state.end_pause(pause_id, &CALENDAR).unwrap();
+
+        let today = state.day_on(CALENDAR.today(), &CALENDAR).unwrap();
+        assert_eq!(today.habits[&run].status, HabitStatus::Pending);
+        assert!(!state.is_paused(run, CALENDAR.today()));
+        // The pause is kept, covering no days, so syncing sees it end
+        let pause = &state.pauses[&pause_id];
+        assert!(pause.end < pause.start);
+    }
+
+    #[test]
+    fn finds_days_by_date() {
+        // Days start at 04:00 in Madrid, so 01:00 UTC is still the day before
+        let calendar = Calendar {
+            timezone: Some(chrono_tz::Europe::Madrid),
+            rollover_hour: 4,
+        };
+        let mut state = State::default();
+        for time in [
+            "2025-03-07T12:00:00Z",
+            "2025-03-05T01:00:00Z",
+            "2025-03-05T12:00:00Z",
+            "2025-02-01T12:00:00Z",
+        ] {
+            state.add_day(time.parse().unwrap(), &calendar).unwrap();
+        }
+        let date = |day: u32| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        let dates = |days: Vec<&Day>| {
+            days.iter()
+                .map(|day| calendar.date_of(day.date).to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            dates(state.days_in(date(4)..=date(7), &calendar)),
+            ["2025-03-04", "2025-03-05", "2025-03-07"]
+        );
+        assert_eq!(
+            dates(state.days_in(date(5)..date(7), &calendar)),
+            ["2025-03-05"]
+        );
+        assert_eq!(state.days_in(.., &calendar).len(), 4);
+        assert_eq!(dates(state.days_in(..date(4), &calendar)), ["2025-02-01"]);
+        assert!(state.day_on(date(6), &calendar).is_none());
+
+        // Moving a day and reading the state back both keep the order up to date
+        let moved = state.day_on(date(7), &calendar).unwrap().id;
+        state
+            .days
+            .set_date(moved, "2025-03-06T12:00:00Z".parse().unwrap());
+        assert_eq!(state.day_on(date(6), &calendar).unwrap().id, moved);
+        assert!(state.day_on(date(7), &calendar).is_none());
+        let state: State = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(state.days_in(date(6)..=date(7), &calendar).len(), 1);
+    }
+
+    #[test]
+    fn renames_only_the_habit() {
+        let (mut state, run, _) = paused_run();
+        let title = || NonEmpty::new_validated("Jog".to_string()).unwrap();
+        state.rename_habit(run, title()).unwrap();
+
+        let day = state.days.values().next().unwrap();
+        assert_eq!(day.habits[&run].name, "Run");
+        assert_eq!(state.habit_name(&day.habits[&run]), "Jog");
+        assert!(state.rename_habit(Uuid::new_v4(), title()).is_err());
+    }
+
+    #[test]
+    fn new_days_leave_archived_habits_out() {
+        let (mut state, run, pause_id) = paused_run();
+        let read = *state.habits.keys().nth(1).unwrap();
+        state.end_pause(pause_id, &CALENDAR).unwrap();
+        state.set_habit_archived(read, true).unwrap();
+        state.add_day(chrono::Utc::now(), &CALENDAR).unwrap();
+
+        let today = state.day_on(CALENDAR.today(), &CALENDAR).unwrap();
+        assert_eq!(today.habits.keys().collect::<Vec<_>>(), [&run]);
+        // Archiving keeps the past
+        let yesterday = CALENDAR.today() - chrono::TimeDelta::days(1);
+        assert!(state
+            .day_on(yesterday, &CALENDAR)
+            .unwrap()
+            .habits
+            .contains_key(&read));
+
+        state.set_habit_archived(read, false).unwrap();
+        assert!(!state.habits[&read].archived);
+        assert!(state.set_habit_archived(Uuid::new_v4(), true).is_err());
+    }
+
+    #[test]
+    fn pages_through_old_days() {
+        let mut state = State::default();
+        let now = chrono::Utc::now();
+        // Nothing in the last few weeks, and a day every other day before that
+        for days_ago in (30..70).step_by(2) {
+            state
+                .add_day(now - chrono::TimeDelta::days(days_ago), &CALENDAR)
+                .unwrap();
+        }
+        let today = CALENDAR.today();
+        let days_ago = |days: i64| today - chrono::TimeDelta::days(days);
+
+        // The first page ends at the latest day rather than at today
+        let first = state.page_start(today.succ_opt().unwrap(), 14, &CALENDAR);
+        assert_eq!(first, Some(days_ago(56)));
+        assert_eq!(state.days_in(first.unwrap().., &CALENDAR).len(), 14);
+        let second = state.page_start(first.unwrap(), 14, &CALENDAR);
+        assert_eq!(second, Some(days_ago(68)));
+        assert_eq!(state.page_start(second.unwrap(), 14, &CALENDAR), None);
+        assert_eq!(State::default().page_start(today, 14, &CALENDAR), None);
+    }
+
+    #[test]
+    fn rejects_invalid_pauses() {
+        let (mut state, _, _) = paused_run();
+        let today = CALENDAR.today();
+        let backwards = Pause::new(today, today - chrono::TimeDelta::days(1), None);
+        assert!(state.add_pause(backwards).is_err());
+        let unknown = Pause::new(today, today, Some(Uuid::new_v4()));
+        assert!(state.add_pause(unknown).is_err());
+        assert!(state.end_pause(Uuid::new_v4(), &CALENDAR).is_err());
+    }
 }
@@ -0,0 +1,154 @@
+use chrono::{Datelike, Months, NaiveDate, TimeDelta};
+use dioxus::prelude::*;
+use std::collections::HashMap;
+
+use crate::components::button::Button;
+use crate::config::Config;
+use crate::db::State;
+use crate::i18n::{self, t};
+use crate::types::HabitStatus;
+use crate::{HabitStatusButton, RitualCmd};
+
+#[component]
+pub fn Week() -> Element {
+    let config = use_context::<Signal<Config>>();
+    let mut weeks_ago = use_signal(|| 0);
+    let today = config.read().calendar().today();
+    let week_start = config.read().week_start;
+    let this_week = today - TimeDelta::days(today.weekday().days_since(week_start).into());
+    let start = this_week - TimeDelta::weeks(weeks_ago());
+    let dates = start.iter_days().take(7).collect::<Vec<_>>();
+    let title = format!("{} – {}", dates[0], dates[6]);
+
+    rsx! {
+        div {
+            class: "main grid-page",
+            PeriodNav {
+                title,
+                on_previous: move |_| weeks_ago += 1,
+                on_next: move |_| weeks_ago -= 1,
+            }
+            HabitGrid { dates }
+        }
+    }
+}
+
+#[component]
+pub fn Month() -> Element {
+    let config = use_context::<Signal<Config>>();
+    let mut months_ago = use_signal(|| 0i32);
+    let today = config.read().calendar().today();
+    let this_month = today.with_day(1).unwrap_or(today);
+    let shift = Months::new(months_ago().unsigned_abs());
+    let start = if months_ago() >= 0 {
+        this_month - shift
+    } else {
+        this_month + shift
+    };
+    let dates = start
+        .iter_days()
+        .take_while(|date| date.month() == start.month())
+        .collect::<Vec<_>>();
+    let title = format!(
+        "{} {}",
+        i18n::month_name(i18n::locale(), start.month()),
+        start.year()
+    );
+
+    rsx! {
+        div {
+            class: "main grid-page",
+            PeriodNav {
+                title,
+                on_previous: move |_| months_ago += 1,
+                on_next: move |_| months_ago -= 1,
+            }
+            HabitGrid { dates }
+        }
+    }
+}
+
+#[component]
+fn PeriodNav(title: String, on_previous: EventHandler, on_next: EventHandler) -> Element {
+    rsx! {
+        div {
+            class: "period",
+            Button {
+                class: "enabled",
+                onclick: move |_| on_previous.call(()),
+                {t!("grid-previous")}
+            }
+            h1 { "{title}" }
+            Button {
+                class: "enabled",
+                onclick: move |_| on_next.call(()),
+                {t!("grid-next")}
+            }
+        }
+    }
+}
+
+/// A habits × days table like a paper tracker. Habits can only be checked on days
+/// that have been added.
+#[component]
+fn HabitGrid(dates: Vec<NaiveDate>) -> Element {
+    let cmd = use_coroutine_handle::<RitualCmd>();
+    let state = use_context::<Signal<State>>();
+    let config = use_context::<Signal<Config>>();
+    let calendar = config.read().calendar();
+    let (Some(first), Some(last)) = (dates.first().copied(), dates.last().copied()) else {
+        return rsx! {};
+    };
+    let state = state.read();
+    let days = state
+        .days_in(first..=last, &calendar)
+        .into_iter()
+        .map(|day| (calendar.date_of(day.date), day))
+        .collect::<HashMap<_, _>>();
+
+    rsx! {
+        table {
+            class: "habit-grid",
+            thead {
+                tr {
+                    th {}
+                    for date in dates.iter() {
+                        th {
+                            key: "{date}",
+                            class: if *date == calendar.today() { "today" },
+                            div { {i18n::weekday_initial(i18n::locale(), date.weekday())} }
+                            div { "{date.day()}" }
+                        }
+                    }
+                }
+            }
+            tbody {
+                for habit in state.habits.values().filter(|habit| !habit.archived) {
+                    tr {
+                        key: "{habit.id}",
+                        style: habit.color.as_ref().map(|color| format!("--accent: {color}")),
+                        th { "{habit.title}" }
+                        for date in dates.iter() {
+                            td {
+                                key: "{date}",
+                                class: if state.is_paused(habit.id, *date) { "paused" },
+                                if let Some((day_id, habit_ref)) = days
+                                    .get(date)
+                                    .and_then(|day| Some((day.id, day.habits.get(&habit.id)?)))
+                                {
+                                    HabitStatusButton {
+                                        status: habit_ref.status,
+                                        onclick: {
+                                            let (habit_id, done) = (habit.id, habit_ref.status != HabitStatus::Done);
+                                            move |_| cmd.send(RitualCmd::HabitSetDone { day_id, habit_id, done })
+                                        },
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
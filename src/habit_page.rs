@@ -0,0 +1,156 @@
+use chrono::{Datelike, Months, NaiveDate};
+use dioxus::prelude::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::components::button::Button;
+use crate::components::charts::LineChart;
+use crate::config::Config;
+use crate::db::State;
+use crate::i18n::{self, t};
+use crate::stats;
+use crate::strength;
+use crate::types::HabitStatus;
+use crate::{HabitOptions, RitualCmd};
+
+#[component]
+pub fn HabitDetail(id: Uuid) -> Element {
+    let cmd = use_coroutine_handle::<RitualCmd>();
+    let state = use_context::<Signal<State>>();
+    let config = use_context::<Signal<Config>>();
+    let calendar = config.read().calendar();
+    let today = calendar.today();
+    let Some(habit) = state.read().habits.get(&id).cloned() else {
+        return rsx! {
+            div { class: "main", p { {t!("habit-not-found")} } }
+        };
+    };
+    let check_ins = stats::check_ins(&state.read(), &calendar, id);
+    let habit_stats = stats::habit_stats(&state.read(), &calendar, today)
+        .into_iter()
+        .find(|stats| stats.habit_id == id);
+    let notes = state
+        .read()
+        .days_in(.., &calendar)
+        .into_iter()
+        .rev()
+        .filter_map(|day| {
+            let note = day.habits.get(&id)?.note.clone()?;
+            Some((calendar.date_of(day.date), note))
+        })
+        .collect::<Vec<_>>();
+    let strength = strength::habit_history(&state.read(), &calendar, id)
+        .into_iter()
+        .map(|(_, score)| Some(score))
+        .collect::<Vec<_>>();
+    let current_strength = strength.last().copied().flatten().unwrap_or(0.0);
+    let archived = habit.archived;
+
+    rsx! {
+        div {
+            class: "main habit-page",
+            style: habit.color.as_ref().map(|color| format!("--accent: {color}")),
+            input {
+                class: "title",
+                r#type: "text",
+                value: "{habit.title}",
+                onchange: move |e| cmd.send(RitualCmd::RenameHabit { habit_id: id, title: e.data.value() }),
+            }
+            if archived {
+                p { class: "archived", {t!("habit-archived")} }
+            }
+            HabitOptions { habit_id: id }
+            if let Some(habit_stats) = habit_stats {
+                dl {
+                    class: "habit-stats",
+                    dt { {t!("stats-current-streak")} }
+                    dd { "{habit_stats.current_streak}" }
+                    dt { {t!("stats-longest-streak")} }
+                    dd { "{habit_stats.longest_streak}" }
+                    dt { {t!("stats-completion")} }
+                    dd { {format!("{:.0}%", habit_stats.completion_rate * 100.0)} }
+                    dt { {t!("habit-strength")} }
+                    dd { {format!("{:.0}%", current_strength * 100.0)} }
+                }
+            }
+            if strength.len() > 1 {
+                LineChart { class: "strength", values: strength }
+            }
+            HistoryCalendar { check_ins }
+            if !notes.is_empty() {
+                h2 { {t!("habit-notes")} }
+                ul {
+                    class: "notes",
+                    for (date, note) in notes {
+                        li {
+                            key: "{date}",
+                            span { class: "date", {i18n::fmt_nice_date(date, today, i18n::locale())} }
+                            "{note}"
+                        }
+                    }
+                }
+            }
+            Button {
+                class: "enabled",
+                onclick: move |_| cmd.send(RitualCmd::SetHabitArchived { habit_id: id, archived: !archived }),
+                if archived { {t!("habit-unarchive")} } else { {t!("habit-archive")} }
+            }
+        }
+    }
+}
+
+/// One small calendar per month with each day colored by how the habit went,
+/// from the month of the first check-in to this one, newest first
+#[component]
+fn HistoryCalendar(check_ins: Vec<(NaiveDate, HabitStatus)>) -> Element {
+    let config = use_context::<Signal<Config>>();
+    let today = config.read().calendar().today();
+    let week_start = config.read().week_start;
+    let statuses = check_ins.iter().copied().collect::<HashMap<_, _>>();
+    let this_month = today.with_day(1).unwrap_or(today);
+    let first_month = check_ins
+        .first()
+        .and_then(|(date, _)| date.with_day(1))
+        .unwrap_or(this_month);
+    let months = std::iter::successors(Some(this_month), |month| {
+        month
+            .checked_sub_months(Months::new(1))
+            .filter(|month| *month >= first_month)
+    });
+    let weekdays = std::iter::successors(Some(week_start), |d| Some(d.succ()))
+        .take(7)
+        .collect::<Vec<_>>();
+
+    rsx! {
+        div {
+            class: "history",
+            for month in months {
+                div {
+                    key: "{month}",
+                    class: "month",
+                    h3 { {format!("{} {}", i18n::month_name(i18n::locale(), month.month()), month.year())} }
+                    div {
+                        class: "days",
+                        for weekday in weekdays.iter() {
+                            span { class: "weekday", {i18n::weekday_initial(i18n::locale(), *weekday)} }
+                        }
+                        for _ in 0..month.weekday().days_since(week_start) {
+                            span {}
+                        }
+                        for date in month.iter_days().take_while(|date| date.month() == month.month()) {
+                            span {
+                                key: "{date}",
+                                class: match statuses.get(&date) {
+                                    Some(status) => format!("day {status}"),
+                                    None => "day".to_string(),
+                                },
+                                title: "{date}",
+                                "{date.day()}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
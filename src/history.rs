@@ -0,0 +1,235 @@
+//! History of the database as commits in a git repository of its own.
+//!
+//! The repository is kept next to the database, as `<db>.history`, with the
+//! database's directory as its work tree, so a repository the directory may
+//! already be part of is left alone. Only the database file is ever added, and the
+//! files the app keeps next to it are excluded. Commits are local, nothing is
+//! pushed.
+
+use color_eyre::eyre;
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use crate::prelude::*;
+use crate::utils;
+
+/// Patterns of the files next to the database that are never committed
+const EXCLUDED: [&str; 5] = ["*.lock", "*.sock", "*.sync", "*.tmp", "*.history/"];
+
+#[derive(Debug, Clone)]
+pub struct History {
+    dir: PathBuf,
+    file: OsString,
+    /// The repository, relative to `dir`
+    git_dir: OsString,
+}
+
+impl History {
+    /// Opens the history of the database at `db_path`, creating its repository if
+    /// there is none
+    #[tracing::instrument]
+    pub fn open(db_path: &Path) -> Result<Self> {
+        let file = db_path
+            .file_name()
+            .ok_or_else(|| eyre::eyre!("{:?} is not a file", db_path))?
+            .to_owned();
+        let dir = match db_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let git_dir = utils::sibling_path(Path::new(&file), "history").into_os_string();
+        let history = Self { dir, file, git_dir };
+        let repository = history.dir.join(&history.git_dir);
+        if !repository.exists() {
+            info!("Creating history repository {:?}", repository);
+            std::fs::create_dir_all(&history.dir)?;
+            history.git(&["init", "--quiet"])?;
+        }
+        history.exclude()?;
+        Ok(history)
+    }
+
+    /// Adds whichever excluded patterns the repository does not have yet, keeping
+    /// any others
+    fn exclude(&self) -> Result<()> {
+        let path = self.dir.join(&self.git_dir).join("info").join("exclude");
+        let mut exclude = match std::fs::read_to_string(&path) {
+            Ok(exclude) => exclude,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let missing = EXCLUDED
+            .iter()
+            .filter(|pattern| !exclude.lines().any(|line| line.trim() == **pattern))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        if !exclude.is_empty() && !exclude.ends_with('\n') {
+            exclude.push('\n');
+        }
+        for pattern in missing {
+            exclude.push_str(pattern);
+            exclude.push('\n');
+        }
+        std::fs::create_dir_all(path.parent().unwrap_or(&self.dir))?;
+        std::fs::write(&path, exclude)?;
+        Ok(())
+    }
+
+    fn git(&self, args: &[&str]) -> Result<Output> {
+        let mut git_dir = OsString::from("--git-dir=");
+        git_dir.push(&self.git_dir);
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.dir)
+            .arg(git_dir)
+            .arg("--work-tree=.")
+            // Commits are made by the app, not whoever configured git on this machine
+            .args([
+                "-c",
+                "user.name=ritual",
+                "-c",
+                "user.email=ritual@localhost",
+            ])
+            .args(args)
+            .output()
+            .map_err(|err| eyre::eyre!("Failed to run git: {}", err))?;
+        if !output.status.success() {
+            return Err(eyre::eyre!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(output)
+    }
+
+    fn file(&self) -> &str {
+        // Only used as a git argument, a lossy name just fails to match
+        self.file.to_str().unwrap_or_default()
+    }
+
+    /// Commits the database as it is on disk, returning whether there was anything
+    /// to commit
+    pub fn commit(&self, message: &str) -> Result<bool> {
+        self.git(&["add", "--", self.file()])?;
+        let staged = self.git(&["diff", "--cached", "--name-only", "--", self.file()])?;
+        if staged.stdout.is_empty() {
+            trace!("Nothing to commit in {:?}", self.dir);
+            return Ok(false);
+        }
+        self.git(&["commit", "--quiet", "-m", message, "--", self.file()])?;
+        info!(
+            "Committed database history: {}",
+            message.lines().next().unwrap_or_default()
+        );
+        Ok(true)
+    }
+
+    /// One line per commit of the database, newest first
+    pub fn log(&self) -> Result<String> {
+        let output = self.git(&[
+            "log",
+            "--format=%h %ad %s",
+            "--date=format:%Y-%m-%d %H:%M",
+            "--",
+            self.file(),
+        ])?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// The database as it was at `rev`
+    pub fn show(&self, rev: &str) -> Result<String> {
+        // Resolved first, so a rev such as `--output=file` can't pass as an option
+        let commit = self
+            .git(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                "--end-of-options",
+                &format!("{}^{{commit}}", rev),
+            ])
+            .map_err(|_| eyre::eyre!("No revision {:?} in the history", rev))?;
+        let commit = String::from_utf8_lossy(&commit.stdout);
+        let output = self.git(&["show", &format!("{}:./{}", commit.trim(), self.file())])?;
+        Ok(String::from_utf8(output.stdout)?)
+    }
+}
+
+/// Commit message for a batch of changes, summarized on the first line
+pub fn message(changes: &[String]) -> String {
+    match changes {
+        [] => "Save".to_string(),
+        [change] => change.clone(),
+        changes => {
+            let list = changes
+                .iter()
+                .map(|change| format!("- {}", change))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("{} changes\n\n{}", changes.len(), list)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_path() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ritual-history-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("db.json")
+    }
+
+    #[test]
+    fn commits_and_shows() {
+        let db_path = db_path();
+        std::fs::write(&db_path, "one").unwrap();
+        let history = History::open(&db_path).unwrap();
+        assert!(history.commit("First").unwrap());
+        assert!(!history.commit("Nothing").unwrap());
+        std::fs::write(&db_path, "two").unwrap();
+        std::fs::write(utils::sibling_path(&db_path, "lock"), "").unwrap();
+        assert!(history.commit("Second").unwrap());
+
+        let log = history.log().unwrap();
+        assert_eq!(log.lines().count(), 2);
+        assert!(log.lines().next().unwrap().ends_with("Second"));
+        assert_eq!(history.show("HEAD~1").unwrap(), "one");
+        let output = db_path.with_extension("out");
+        let rev = format!("--output={}", output.display());
+        assert!(history.show(&rev).is_err());
+        assert!(!output.exists());
+        assert!(history.show("HEAD~5").is_err());
+        // The directory is not made into a repository of its own
+        assert!(!db_path.parent().unwrap().join(".git").exists());
+    }
+
+    #[test]
+    fn keeps_existing_excludes() {
+        let db_path = db_path();
+        History::open(&db_path).unwrap();
+        let exclude = utils::sibling_path(&db_path, "history").join("info/exclude");
+        std::fs::write(&exclude, "*.bak").unwrap();
+        History::open(&db_path).unwrap();
+        let exclude = std::fs::read_to_string(&exclude).unwrap();
+        assert!(exclude.starts_with("*.bak\n"));
+        for pattern in EXCLUDED {
+            assert_eq!(exclude.lines().filter(|line| *line == pattern).count(), 1);
+        }
+    }
+
+    #[test]
+    fn summarizes_changes() {
+        assert_eq!(message(&[]), "Save");
+        assert_eq!(message(&["Check off Run".to_string()]), "Check off Run");
+        assert_eq!(
+            message(&["Check off Run".to_string(), "Add Read".to_string()]),
+            "2 changes\n\n- Check off Run\n- Add Read"
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use intl_pluralrules::{PluralCategory, PluralRuleType, PluralRules};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{LazyLock, RwLock};
+use unic_langid::LanguageIdentifier;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Es];
+
+    fn langid(&self) -> LanguageIdentifier {
+        match self {
+            Locale::En => unic_langid::langid!("en"),
+            Locale::Es => unic_langid::langid!("es"),
+        }
+    }
+
+    fn catalog(&self) -> &'static str {
+        match self {
+            Locale::En => include_str!("../locales/en.ftl"),
+            Locale::Es => include_str!("../locales/es.ftl"),
+        }
+    }
+
+    /// The language's name in that language, for the locale picker
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Es => "Español",
+        }
+    }
+
+    /// Best match for the system language, English if there is none
+    pub fn from_system() -> Self {
+        sys_locale::get_locale()
+            .and_then(|tag| tag.split(['-', '_']).next()?.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Locale::En => write!(f, "en"),
+            Locale::Es => write!(f, "es"),
+        }
+    }
+}
+
+impl std::str::FromStr for Locale {
+    type Err = color_eyre::eyre::Report;
+    fn from_str(s: &str) -> Result<Self> {
+        Locale::ALL
+            .into_iter()
+            .find(|l| l.to_string() == s)
+            .ok_or_else(|| color_eyre::eyre::eyre!("Unknown locale {}", s))
+    }
+}
+
+static BUNDLES: LazyLock<HashMap<Locale, FluentBundle<FluentResource>>> = LazyLock::new(|| {
+    Locale::ALL
+        .into_iter()
+        .map(|locale| (locale, bundle(locale)))
+        .collect()
+});
+
+static CURRENT: RwLock<Locale> = RwLock::new(Locale::En);
+
+fn bundle(locale: Locale) -> FluentBundle<FluentResource> {
+    let resource = FluentResource::try_new(locale.catalog().to_string())
+        .unwrap_or_else(|(_, errors)| panic!("Invalid {} catalog: {:?}", locale, errors));
+    let mut bundle = FluentBundle::new_concurrent(vec![locale.langid()]);
+    // Unicode isolation marks around placeables only get in the way of plain text UIs
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .expect("Duplicate messages in catalog");
+
+    let ordinals = PluralRules::create(locale.langid(), PluralRuleType::ORDINAL)
+        .expect("Missing ordinal rules for locale");
+    bundle
+        .add_function("ORDINAL", move |positional, _named| {
+            let Some(FluentValue::Number(n)) = positional.first() else {
+                return FluentValue::Error;
+            };
+            let category = match ordinals.select(n.value) {
+                Ok(PluralCategory::ZERO) => "zero",
+                Ok(PluralCategory::ONE) => "one",
+                Ok(PluralCategory::TWO) => "two",
+                Ok(PluralCategory::FEW) => "few",
+                Ok(PluralCategory::MANY) => "many",
+                _ => "other",
+            };
+            FluentValue::from(category)
+        })
+        .expect("ORDINAL function registered twice");
+    bundle
+}
+
+pub fn locale() -> Locale {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set_locale(locale: Locale) {
+    info!("Setting locale to {}", locale);
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = locale;
+}
+
+/// Looks up a message, falling back to the key itself if it is missing
+pub fn translate(locale: Locale, key: &str, args: Option<&FluentArgs>) -> String {
+    let bundle = &BUNDLES[&locale];
+    let Some(pattern) = bundle.get_message(key).and_then(|m| m.value()) else {
+        warn!("Missing {} translation for {}", locale, key);
+        return key.to_string();
+    };
+    let mut errors = Vec::new();
+    let value = bundle.format_pattern(pattern, args, &mut errors);
+    if !errors.is_empty() {
+        warn!("Errors formatting {} message {}: {:?}", locale, key, errors);
+    }
+    value.into_owned()
+}
+
+/// Translates a message into the current locale, e.g. `t!("save")` or
+/// `t!("settings-save-failed", error = err.to_string())`
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::translate($crate::i18n::locale(), $key, None)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::translate($crate::i18n::locale(), $key, Some(&args))
+    }};
+}
+pub(crate) use t;
+
+fn weekday_key(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "mon",
+        Weekday::Tue => "tue",
+        Weekday::Wed => "wed",
+        Weekday::Thu => "thu",
+        Weekday::Fri => "fri",
+        Weekday::Sat => "sat",
+        Weekday::Sun => "sun",
+    }
+}
+
+pub fn weekday_name(locale: Locale, weekday: Weekday) -> String {
+    translate(locale, &format!("weekday-{}", weekday_key(weekday)), None)
+}
+
+pub fn weekday_initial(locale: Locale, weekday: Weekday) -> String {
+    translate(
+        locale,
+        &format!("weekday-short-{}", weekday_key(weekday)),
+        None,
+    )
+}
+
+pub fn month_name(locale: Locale, month: u32) -> String {
+    translate(locale, &format!("month-{}", month), None)
+}
+
+/// Formats a date relative to today, e.g. "Yesterday", "Friday", "21st" or "3 of March, 2024"
+pub fn fmt_nice_date(date: NaiveDate, today: NaiveDate, locale: Locale) -> String {
+    let days_ago = today.signed_duration_since(date).num_days();
+    let mut args = FluentArgs::new();
+    args.set("day", date.day());
+
+    match days_ago {
+        0 => translate(locale, "date-today", None),
+        1 => translate(locale, "date-yesterday", None),
+        2..=6 => weekday_name(locale, date.weekday()),
+        _ if date.month() == today.month() && date.year() == today.year() => {
+            translate(locale, "date-this-month", Some(&args))
+        }
+        _ => {
+            args.set("month", month_name(locale, date.month()));
+            args.set("year", date.year());
+            translate(locale, "date-full", Some(&args))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn relative_days_en() {
+        let today = date(2025, 3, 28);
+        assert_eq!(fmt_nice_date(today, today, Locale::En), "Today");
+        assert_eq!(
+            fmt_nice_date(date(2025, 3, 27), today, Locale::En),
+            "Yesterday"
+        );
+        assert_eq!(
+            fmt_nice_date(date(2025, 3, 24), today, Locale::En),
+            "Monday"
+        );
+    }
+
+    #[test]
+    fn ordinals_en() {
+        let today = date(2025, 3, 31);
+        let cases = [
+            (1, "1st"),
+            (2, "2nd"),
+            (3, "3rd"),
+            (4, "4th"),
+            (11, "11th"),
+            (12, "12th"),
+            (13, "13th"),
+            (21, "21st"),
+            (22, "22nd"),
+            (23, "23rd"),
+        ];
+        for (day, expected) in cases {
+            assert_eq!(
+                fmt_nice_date(date(2025, 3, day), today, Locale::En),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn full_date_en() {
+        assert_eq!(
+            fmt_nice_date(date(2024, 2, 3), date(2025, 3, 28), Locale::En),
+            "3 of February, 2024"
+        );
+    }
+
+    #[test]
+    fn dates_es() {
+        let today = date(2025, 3, 28);
+        assert_eq!(fmt_nice_date(today, today, Locale::Es), "Hoy");
+        assert_eq!(fmt_nice_date(date(2025, 3, 27), today, Locale::Es), "Ayer");
+        assert_eq!(
+            fmt_nice_date(date(2025, 3, 26), today, Locale::Es),
+            "Miércoles"
+        );
+        assert_eq!(fmt_nice_date(date(2025, 3, 2), today, Locale::Es), "día 2");
+        assert_eq!(
+            fmt_nice_date(date(2024, 12, 25), today, Locale::Es),
+            "25 de diciembre de 2024"
+        );
+    }
+
+    #[test]
+    fn catalogs_have_the_same_messages() {
+        let ids = |locale: Locale| {
+            locale
+                .catalog()
+                .lines()
+                .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+                .filter_map(|line| line.split_once(" =").map(|(id, _)| id.to_string()))
+                .collect::<std::collections::BTreeSet<_>>()
+        };
+        assert_eq!(ids(Locale::En), ids(Locale::Es));
+    }
+}
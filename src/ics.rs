@@ -0,0 +1,217 @@
+//! iCalendar export, to see habits in a calendar app.
+//!
+//! Each habit that is still active becomes a weekly recurring event on its
+//! scheduled weekdays, at its reminder time if it has one and all day otherwise.
+//! Every done check-in becomes an all-day event of its own. UIDs are derived from
+//! the habit and day ids so that re-importing or subscribing updates the events
+//! instead of duplicating them.
+
+use chrono::{Datelike, NaiveDate, TimeDelta, Weekday};
+
+use crate::config::Calendar;
+use crate::db::State;
+use crate::types::{DateTime, Habit, HabitStatus};
+
+/// Longest a content line may be, in bytes, before it is folded
+const LINE_LIMIT: usize = 75;
+
+/// The whole calendar, `now` being when it was generated
+pub fn export(state: &State, calendar: &Calendar, now: DateTime) -> String {
+    let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Ritual//Ritual//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Ritual".to_string(),
+    ];
+    for habit in state.habits.values() {
+        if habit.archived || habit.schedule.weekdays.is_empty() {
+            continue;
+        }
+        let start = state
+            .days
+            .values()
+            .filter(|day| day.habits.contains_key(&habit.id))
+            .map(|day| calendar.date_of(day.date))
+            .min()
+            .unwrap_or_else(|| calendar.today());
+        lines.extend(schedule_event(habit, start, &stamp));
+    }
+    for day in state.days.values() {
+        let date = calendar.date_of(day.date);
+        for habit_ref in day.habits.values() {
+            if habit_ref.status != HabitStatus::Done {
+                continue;
+            }
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:{}-{}@ritual", day.id, habit_ref.id));
+            lines.push(format!("DTSTAMP:{stamp}"));
+            lines.push(format!("DTSTART;VALUE=DATE:{}", date_value(date)));
+            lines.push(format!(
+                "DTEND;VALUE=DATE:{}",
+                date_value(date + TimeDelta::days(1))
+            ));
+            lines.push(format!("SUMMARY:✓ {}", escape(state.habit_name(habit_ref))));
+            if let Some(note) = &habit_ref.note {
+                lines.push(format!("DESCRIPTION:{}", escape(note)));
+            }
+            lines.push("TRANSP:TRANSPARENT".to_string());
+            lines.push("END:VEVENT".to_string());
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().fold(String::new(), |mut ics, line| {
+        ics.push_str(&fold(line));
+        ics.push_str("\r\n");
+        ics
+    })
+}
+
+fn schedule_event(habit: &Habit, start: NaiveDate, stamp: &str) -> Vec<String> {
+    // Start on the first scheduled day, which the recurrence rule expects
+    let start = start
+        .iter_days()
+        .find(|date| habit.schedule.includes(*date))
+        .unwrap_or(start);
+    let by_day = habit
+        .schedule
+        .weekdays
+        .iter()
+        .map(|weekday| weekday_value(*weekday))
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@ritual", habit.id),
+        format!("DTSTAMP:{stamp}"),
+    ];
+    match habit.reminder {
+        // Floating time, so it stays at the same local time wherever one is
+        Some(time) => {
+            lines.push(format!(
+                "DTSTART:{}T{}",
+                date_value(start),
+                time.format("%H%M%S")
+            ));
+            lines.push("DURATION:PT15M".to_string());
+        }
+        None => {
+            lines.push(format!("DTSTART;VALUE=DATE:{}", date_value(start)));
+            lines.push("DURATION:P1D".to_string());
+        }
+    }
+    lines.push(format!("RRULE:FREQ=WEEKLY;BYDAY={by_day}"));
+    lines.push(format!("SUMMARY:{}", escape(&habit.title)));
+    lines.push("TRANSP:TRANSPARENT".to_string());
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+fn date_value(date: NaiveDate) -> String {
+    format!("{:04}{:02}{:02}", date.year(), date.month(), date.day())
+}
+
+fn weekday_value(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+/// Escapes a TEXT value
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Splits a line into parts of at most 75 bytes, continued with a space on the
+/// next line, without splitting characters
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the continued line
+            length = 1;
+        }
+        length += c.len_utf8();
+        folded.push(c);
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{NonEmpty, Validate};
+
+    #[test]
+    fn escapes_and_folds() {
+        assert_eq!(
+            escape("Run, stretch; rest\\\nrepeat"),
+            "Run\\, stretch\\; rest\\\\\\nrepeat"
+        );
+        let line = format!("SUMMARY:{}", "é".repeat(60));
+        let folded = fold(&line);
+        assert!(folded.split("\r\n").all(|part| part.len() <= LINE_LIMIT));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+        assert_eq!(fold("SUMMARY:Run"), "SUMMARY:Run");
+    }
+
+    #[test]
+    fn exports_schedule_and_check_ins() {
+        let calendar = Calendar {
+            timezone: Some(chrono_tz::UTC),
+            rollover_hour: 0,
+        };
+        // A Wednesday
+        let now = "2025-03-05T12:00:00Z".parse::<DateTime>().unwrap();
+        let mut state = State::default();
+        state.add_day(now, &calendar).unwrap();
+        let day_id = *state.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Run, far".to_string()).unwrap();
+        state.add_habit_to_day(title, day_id).unwrap();
+        let habit_id = *state.habits.keys().next().unwrap();
+        state.habits[&habit_id].schedule.weekdays = vec![Weekday::Mon, Weekday::Thu];
+        state
+            .set_habit_status(day_id, habit_id, HabitStatus::Done)
+            .unwrap();
+
+        let ics = export(&state, &calendar, now);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains(&format!("UID:{habit_id}@ritual\r\n")));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250306\r\nDURATION:P1D\r\n"));
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;BYDAY=MO,TH\r\n"));
+        assert!(ics.contains("SUMMARY:Run\\, far\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250305\r\nDTEND;VALUE=DATE:20250306\r\n"));
+        assert!(ics.contains("SUMMARY:✓ Run\\, far\r\n"));
+
+        state.habits[&habit_id].reminder = chrono::NaiveTime::from_hms_opt(7, 30, 0);
+        let ics = export(&state, &calendar, now);
+        assert!(ics.contains("DTSTART:20250306T073000\r\nDURATION:PT15M\r\n"));
+
+        state.habits[&habit_id].archived = true;
+        let ics = export(&state, &calendar, now);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+    }
+}
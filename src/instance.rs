@@ -0,0 +1,285 @@
+//! Keeps a single process in charge of a database.
+//!
+//! Whoever holds the lock file next to `db.json` owns it. The running app also
+//! listens on a Unix socket there, so CLI invocations can hand their commands to
+//! it instead of writing the file underneath it. Every message is answered, so a
+//! client learns when the app refuses it, e.g. while its database is locked.
+//! Sockets are only available on Unix, elsewhere the lock alone keeps a second
+//! process out.
+
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::Path;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::db::State;
+use crate::prelude::*;
+use crate::utils::sibling_path;
+use crate::RitualCmd;
+
+/// A line sent by a client over the socket
+#[derive(Debug, Serialize, Deserialize)]
+enum Message {
+    Cmd(RitualCmd),
+    /// Asks for the current state, answered with a line of JSON
+    GetState,
+}
+
+/// The answer to a message, a line of JSON
+#[derive(Debug, Serialize, Deserialize)]
+enum Reply {
+    Accepted,
+    State(State),
+    Refused(String),
+}
+
+/// A client message for the app to handle, answered with why it was refused if
+/// it was
+#[derive(Debug)]
+pub enum IpcRequest {
+    Cmd {
+        cmd: RitualCmd,
+        reply: oneshot::Sender<std::result::Result<(), String>>,
+    },
+    State {
+        reply: oneshot::Sender<std::result::Result<State, String>>,
+    },
+}
+
+/// Exclusive ownership of a database, released when dropped
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Takes the lock, failing if another process already holds it
+    pub fn acquire(db_path: &Path) -> Result<Self> {
+        let path = sibling_path(db_path, "lock");
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        file.try_lock().map_err(|err| {
+            eyre::eyre!(
+                "Database {:?} is in use by another process: {}",
+                db_path,
+                err
+            )
+        })?;
+        trace!("Locked {:?}", path);
+        Ok(Self { _file: file })
+    }
+}
+
+/// Listens for clients of the database at `db_path`, forwarding their messages to
+/// the returned channel. Only the holder of its [`InstanceLock`] should listen.
+#[cfg(unix)]
+pub fn listen(db_path: &Path) -> Result<mpsc::UnboundedReceiver<IpcRequest>> {
+    let path = sibling_path(db_path, "sock");
+    // Holding the lock means any socket left there is stale
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    info!("Listening for commands on {:?}", path);
+    let (requests, incoming) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                // Nobody is handling requests for this database anymore
+                _ = requests.closed() => break,
+            };
+            match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve_client(stream, requests.clone()));
+                }
+                Err(err) => {
+                    error!("Failed to accept connection: {}", err);
+                    break;
+                }
+            }
+        }
+        trace!("Stopped listening on {:?}", path);
+        let _ = std::fs::remove_file(&path);
+    });
+    Ok(incoming)
+}
+
+#[cfg(unix)]
+async fn serve_client(stream: tokio::net::UnixStream, requests: mpsc::UnboundedSender<IpcRequest>) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let message = match serde_json::from_str::<Message>(&line) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!("Ignoring invalid message {:?}: {}", line, err);
+                continue;
+            }
+        };
+        trace!("Received {:?}", message);
+        let reply = match message {
+            Message::Cmd(cmd) => {
+                let (reply, response) = oneshot::channel();
+                let _ = requests.send(IpcRequest::Cmd { cmd, reply });
+                response.await.map(|accepted| match accepted {
+                    Ok(()) => Reply::Accepted,
+                    Err(reason) => Reply::Refused(reason),
+                })
+            }
+            Message::GetState => {
+                let (reply, response) = oneshot::channel();
+                let _ = requests.send(IpcRequest::State { reply });
+                response.await.map(|state| match state {
+                    Ok(state) => Reply::State(state),
+                    Err(reason) => Reply::Refused(reason),
+                })
+            }
+        };
+        let Ok(reply) = reply else {
+            break;
+        };
+        let Ok(mut json) = serde_json::to_string(&reply) else {
+            break;
+        };
+        json.push('\n');
+        if writer.write_all(json.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// A connection to the app running on a database
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct Client {
+    stream: std::io::BufReader<std::os::unix::net::UnixStream>,
+}
+
+#[cfg(unix)]
+impl Client {
+    /// Connects to the running app, if there is one
+    pub fn connect(db_path: &Path) -> Result<Option<Self>> {
+        use std::io::ErrorKind;
+
+        match std::os::unix::net::UnixStream::connect(sibling_path(db_path, "sock")) {
+            Ok(stream) => Ok(Some(Self {
+                stream: std::io::BufReader::new(stream),
+            })),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    ErrorKind::NotFound | ErrorKind::ConnectionRefused
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn request(&mut self, message: &Message) -> Result<Reply> {
+        use std::io::{BufRead, Write};
+
+        let mut json = serde_json::to_string(message)?;
+        json.push('\n');
+        self.stream.get_mut().write_all(json.as_bytes())?;
+        let mut line = String::new();
+        if self.stream.read_line(&mut line)? == 0 {
+            return Err(eyre::eyre!("The running app closed the connection"));
+        }
+        match serde_json::from_str(&line)? {
+            Reply::Refused(reason) => Err(eyre::eyre!("The running app refused: {}", reason)),
+            reply => Ok(reply),
+        }
+    }
+
+    pub fn send(&mut self, cmd: RitualCmd) -> Result<()> {
+        match self.request(&Message::Cmd(cmd))? {
+            Reply::Accepted => Ok(()),
+            _ => Err(eyre::eyre!("Unexpected reply from the running app")),
+        }
+    }
+
+    /// The app's current state, including changes it has not saved yet
+    pub fn state(&mut self) -> Result<State> {
+        match self.request(&Message::GetState)? {
+            Reply::State(state) => Ok(state),
+            _ => Err(eyre::eyre!("Unexpected reply from the running app")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_path(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ritual-instance-{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("db.json")
+    }
+
+    #[test]
+    fn lock_is_exclusive() {
+        let path = db_path("lock");
+        let lock = InstanceLock::acquire(&path).unwrap();
+        assert!(InstanceLock::acquire(&path).is_err());
+        drop(lock);
+        InstanceLock::acquire(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn forwards_commands_and_state() {
+        let path = db_path("ipc");
+        assert!(Client::connect(&path).unwrap().is_none());
+        let mut incoming = listen(&path).unwrap();
+        let app = tokio::spawn(async move {
+            let mut cmds = Vec::new();
+            while let Some(request) = incoming.recv().await {
+                match request {
+                    // Saving stands in for whatever the app turns down
+                    IpcRequest::Cmd {
+                        cmd: RitualCmd::Save,
+                        reply,
+                    } => {
+                        let _ = reply.send(Err("Locked".to_string()));
+                    }
+                    IpcRequest::Cmd { cmd, reply } => {
+                        cmds.push(cmd);
+                        let _ = reply.send(Ok(()));
+                    }
+                    IpcRequest::State { reply } => {
+                        let _ = reply.send(Ok(State::default()));
+                        return cmds;
+                    }
+                }
+            }
+            cmds
+        });
+
+        let client_path = path.clone();
+        let state = tokio::task::spawn_blocking(move || {
+            let mut client = Client::connect(&client_path).unwrap().unwrap();
+            client.send(RitualCmd::NewDay).unwrap();
+            let refused = client.send(RitualCmd::Save).unwrap_err();
+            assert!(refused.to_string().contains("Locked"));
+            client.state().unwrap()
+        })
+        .await
+        .unwrap();
+        assert!(state.days.is_empty());
+        assert_eq!(app.await.unwrap(), [RitualCmd::NewDay]);
+    }
+}
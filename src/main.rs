@@ -1,20 +1,67 @@
-use chrono::Datelike;
+use chrono::{NaiveDate, NaiveTime};
+use color_eyre::eyre::{self, WrapErr};
 use components::{button::Button, icon::Icon};
+use config::{Config, ConfigSource, Shortcut};
+use crypto::Passphrase;
 use db::{Db, State};
-use dioxus::{desktop::LogicalSize, html::button::disabled, prelude::*};
-use dioxus_free_icons::icons::io_icons::{IoAddOutline, IoCheckmarkOutline, IoCloseOutline};
+use dioxus::prelude::*;
+use dioxus_free_icons::icons::io_icons::{
+    IoAddOutline, IoCheckmarkOutline, IoCloseOutline, IoEllipsisHorizontal, IoRemoveOutline,
+};
 use futures_util::StreamExt;
-use itertools::Itertools;
+use grid_page::{Month, Week};
+use habit_page::HabitDetail;
+use i18n::t;
 use prelude::*;
-use types::DateTime;
-use utils::{NonEmpty, Validate};
+use reminders::Scheduler;
+use search_page::Search;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use stats_page::Stats;
+use types::{DateTime, HabitStatus};
+use utils::{HexColor, NonEmpty, Validate};
 use uuid::Uuid;
 
+#[cfg(not(feature = "web"))]
+mod api;
+#[cfg(not(feature = "web"))]
 mod cli;
 mod components;
+mod config;
+mod crypto;
+#[cfg(not(feature = "web"))]
+mod daily_notes;
 mod db;
+mod grid_page;
+mod habit_page;
+#[cfg(not(feature = "web"))]
+mod history;
+mod i18n;
+#[cfg(not(feature = "web"))]
+mod ics;
+#[cfg(not(feature = "web"))]
+mod instance;
+mod markdown;
+#[cfg(not(feature = "web"))]
+mod merge;
+mod reminders;
+mod search;
+mod search_page;
+mod settings;
+mod stats;
+mod stats_page;
+mod storage;
+mod strength;
+#[cfg(not(feature = "web"))]
+mod sync;
+#[cfg(test)]
+mod test_utils;
 mod types;
 mod utils;
+#[cfg(not(feature = "web"))]
+mod watch;
+#[cfg(feature = "desktop")]
+mod window_state;
 
 #[allow(unused_imports)]
 pub(crate) mod prelude {
@@ -25,215 +72,1377 @@ pub(crate) mod prelude {
 #[derive(Debug, Clone, Routable, PartialEq)]
 #[rustfmt::skip]
 enum Route {
-    #[route("/")]
-    Home {},
+    #[layout(Layout)]
+        #[route("/")]
+        Home {},
+        #[route("/week")]
+        Week {},
+        #[route("/month")]
+        Month {},
+        #[route("/habit/:id")]
+        HabitDetail { id: Uuid },
+        #[route("/search")]
+        Search {},
+        #[route("/stats")]
+        Stats {},
+        #[route("/settings")]
+        Settings {},
 }
 
 const NORMALIZE_CSS: Asset = asset!("/assets/normalize.css");
 const REMOVE_DEFAULT_STYLES_CSS: Asset = asset!("/assets/remove_default_styles.css");
 const MAIN_CSS: Asset = asset!("/assets/main.css");
 
+#[cfg(not(feature = "web"))]
 fn main() {
-    let args = cli::ParsedArgs::parse_raw();
+    let args = match cli::ParsedArgs::parse_raw() {
+        Ok(args) => args,
+        // The logger is set up from the arguments, so it cannot report this
+        Err(err) => {
+            eprintln!("Error: {:#}", err);
+            std::process::exit(1);
+        }
+    };
 
     dioxus::logger::init(args.log_level).expect("failed to init logger");
+    i18n::set_locale(args.config.locale());
+    config::migrate_legacy_db(&args.config);
+
+    if let Some(command) = args.command {
+        if let Err(err) = run_command(command, args.config, &args.config_path, args.passphrase) {
+            error!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Fail before opening a window if another process has the database. The app
+    // takes the lock again for as long as it uses the database.
+    if let Err(err) = instance::InstanceLock::acquire(&args.config.database()) {
+        error!("{}", err);
+        std::process::exit(1);
+    }
+    let source = ConfigSource {
+        path: args.config_path,
+        overrides: args.overrides,
+    };
+    launch(args.config, source, args.passphrase);
+}
+
+#[cfg(feature = "web")]
+fn main() {
+    dioxus::logger::init(tracing::Level::INFO).expect("failed to init logger");
+
+    let source = ConfigSource {
+        path: config::default_config_path(),
+        overrides: Default::default(),
+    };
+    let config = Config::load(&source.path).unwrap_or_else(|err| {
+        error!("Failed to load config, using defaults: {}", err);
+        Config::default()
+    });
+    i18n::set_locale(config.locale());
+    launch(config, source, None);
+}
+
+/// `passphrase` unlocks an encrypted database without asking for it
+#[cfg(feature = "desktop")]
+fn launch(config: Config, source: ConfigSource, passphrase: Option<Passphrase>) {
     dioxus::LaunchBuilder::desktop()
+        .with_context(source)
+        .with_context(config)
+        .with_context(passphrase)
         .with_cfg(
             dioxus::desktop::Config::default().with_window(
-                dioxus::desktop::WindowBuilder::new()
-                    .with_title("ritual")
-                    .with_inner_size(LogicalSize::new(1000, 720))
-                    .with_decorations(false),
+                window_state::WindowState::load().apply(
+                    dioxus::desktop::WindowBuilder::new()
+                        .with_title("ritual")
+                        .with_decorations(false),
+                ),
             ),
         )
         .launch(App);
 }
 
+#[cfg(not(feature = "desktop"))]
+fn launch(config: Config, source: ConfigSource, passphrase: Option<Passphrase>) {
+    dioxus::LaunchBuilder::new()
+        .with_context(source)
+        .with_context(config)
+        .with_context(passphrase)
+        .launch(App);
+}
+
+#[cfg(not(feature = "web"))]
+fn run_command(
+    command: cli::Command,
+    config: Config,
+    config_path: &std::path::Path,
+    passphrase: Option<Passphrase>,
+) -> Result<()> {
+    use reminders::{DesktopNotifier, WriterNotifier};
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    match command {
+        cli::Command::Daemon {
+            stdout,
+            notify_file,
+        } => {
+            let db = unlocked(Db::open_or_new(config.database())?, passphrase.as_ref())?;
+            let calendar = config.calendar();
+            rt.block_on(async move {
+                if stdout {
+                    reminders::run_daemon(db, WriterNotifier::stdout(), calendar).await
+                } else if let Some(path) = notify_file {
+                    reminders::run_daemon(db, WriterNotifier::file(&path)?, calendar).await
+                } else {
+                    reminders::run_daemon(db, DesktopNotifier, calendar).await
+                }
+            })
+        }
+        cli::Command::NewDay => edit_db(&config, passphrase.as_ref(), |state, calendar| {
+            if state.day_on(calendar.today(), calendar).is_some() {
+                return Err(eyre::eyre!("Today has already been added"));
+            }
+            Ok(RitualCmd::NewDay)
+        }),
+        cli::Command::Add { title } => edit_db(&config, passphrase.as_ref(), |state, calendar| {
+            Ok(RitualCmd::AddHabitToDay {
+                title,
+                day_id: cli_today(state, calendar)?.id,
+            })
+        }),
+        cli::Command::Check { habit } => {
+            edit_db(&config, passphrase.as_ref(), |state, calendar| {
+                cli_set_status(state, calendar, &habit, HabitStatus::Done)
+            })
+        }
+        cli::Command::Uncheck { habit } => {
+            edit_db(&config, passphrase.as_ref(), |state, calendar| {
+                cli_set_status(state, calendar, &habit, HabitStatus::Pending)
+            })
+        }
+        cli::Command::Skip { habit } => edit_db(&config, passphrase.as_ref(), |state, calendar| {
+            cli_set_status(state, calendar, &habit, HabitStatus::Skipped)
+        }),
+        cli::Command::Fail { habit } => edit_db(&config, passphrase.as_ref(), |state, calendar| {
+            cli_set_status(state, calendar, &habit, HabitStatus::Failed)
+        }),
+        cli::Command::Pause { until, from, habit } => {
+            edit_db(&config, passphrase.as_ref(), |state, calendar| {
+                let habit_id = habit.map(|habit| cli_habit(state, &habit)).transpose()?;
+                Ok(RitualCmd::AddPause {
+                    start: from.unwrap_or_else(|| calendar.today()),
+                    end: until,
+                    habit_id,
+                })
+            })
+        }
+        cli::Command::Resume { habit } => {
+            edit_db(&config, passphrase.as_ref(), |state, calendar| {
+                let habit_id = habit.map(|habit| cli_habit(state, &habit)).transpose()?;
+                let today = calendar.today();
+                let pauses = state
+                    .pauses
+                    .values()
+                    .filter(|pause| pause.is_on(today) && pause.habit_id == habit_id)
+                    .collect::<Vec<_>>();
+                match pauses.as_slice() {
+                    [pause] => Ok(RitualCmd::EndPause { pause_id: pause.id }),
+                    [] => Err(eyre::eyre!("Nothing is paused today")),
+                    _ => Err(eyre::eyre!("Several pauses are on today")),
+                }
+            })
+        }
+        cli::Command::Sync => sync_db(&config, passphrase.as_ref()),
+        cli::Command::Merge {
+            first,
+            second,
+            output,
+            policy,
+        } => {
+            let calendar = config.calendar();
+            let first = unlocked(Db::open(first)?, passphrase.as_ref())?;
+            let second = unlocked(Db::open(second)?, passphrase.as_ref())?;
+            let (merged, conflicts) =
+                merge::merge(&first.load()?, &second.load()?, policy, &calendar);
+            for conflict in &conflicts {
+                println!("{}", conflict);
+            }
+            println!(
+                "Merged {} days and {} habits into {}, resolving {} conflicts",
+                merged.days.len(),
+                merged.habits.len(),
+                output.display(),
+                conflicts.len()
+            );
+            let mut output = Db::open_or_new(output)?;
+            // Keep the merged copy as private as the ones it came from
+            if first.cipher().is_some() || second.cipher().is_some() {
+                output.set_passphrase(passphrase.as_ref())?;
+            }
+            output.save(&merged)
+        }
+        cli::Command::Archive { habit } => edit_db(&config, passphrase.as_ref(), |state, _| {
+            Ok(RitualCmd::SetHabitArchived {
+                habit_id: cli_habit(state, &habit)?,
+                archived: true,
+            })
+        }),
+        cli::Command::Unarchive { habit } => edit_db(&config, passphrase.as_ref(), |state, _| {
+            Ok(RitualCmd::SetHabitArchived {
+                habit_id: cli_habit(state, &habit)?,
+                archived: false,
+            })
+        }),
+        cli::Command::Note { habit, note } => {
+            edit_db(&config, passphrase.as_ref(), |state, calendar| {
+                let day = cli_today(state, calendar)?;
+                let habit_id = cli_habit(state, &habit)?;
+                Ok(RitualCmd::SetHabitNote {
+                    day_id: day.id,
+                    habit_id,
+                    note,
+                })
+            })
+        }
+        cli::Command::Search { query } => {
+            use std::io::IsTerminal;
+
+            let query = query.join(" ").parse::<search::Query>()?;
+            let db = unlocked(Db::open(config.database())?, passphrase.as_ref())?;
+            let index = search::Index::new(&db.load()?, &config.calendar());
+            let bold = std::io::stdout().is_terminal();
+            for hit in index.search(&query) {
+                let snippet = hit
+                    .snippet
+                    .iter()
+                    .map(|segment| {
+                        if segment.highlighted && bold {
+                            format!("\x1b[1m{}\x1b[0m", segment.text)
+                        } else {
+                            segment.text.clone()
+                        }
+                    })
+                    .collect::<String>();
+                match &hit.habit {
+                    Some(habit) => println!("{}  [{}] {}", hit.date, habit.status, snippet),
+                    None => println!("{}  [journal] {}", hit.date, snippet),
+                }
+            }
+            Ok(())
+        }
+        cli::Command::ExportIcs { output } => {
+            let db = unlocked(Db::open(config.database())?, passphrase.as_ref())?;
+            let ics = ics::export(&db.load()?, &config.calendar(), chrono::Utc::now());
+            match output {
+                Some(output) => std::fs::write(&output, ics)
+                    .wrap_err_with(|| format!("Failed to write {}", output.display()))?,
+                None => print!("{ics}"),
+            }
+            Ok(())
+        }
+        cli::Command::ExportNotes { from, to, folder } => {
+            let calendar = config.calendar();
+            let to = to.unwrap_or_else(|| calendar.today());
+            let mut daily_notes = config.daily_notes.clone();
+            if folder.is_some() {
+                daily_notes.folder = folder;
+            }
+            let db = unlocked(Db::open(config.database())?, passphrase.as_ref())?;
+            let changed =
+                daily_notes::export(&db.load()?, &calendar, &daily_notes, from.unwrap_or(to), to)?;
+            info!("Updated {} daily notes", changed);
+            Ok(())
+        }
+        cli::Command::Log => {
+            print!("{}", history::History::open(&config.database())?.log()?);
+            Ok(())
+        }
+        cli::Command::Restore { rev } => {
+            let _lock = instance::InstanceLock::acquire(&config.database())?;
+            let history = history::History::open(&config.database())?;
+            let db = unlocked(Db::open_or_new(config.database())?, passphrase.as_ref())?;
+            let state = db.parse(&history.show(&rev)?)?;
+            db.save(&state)?;
+            history.commit(&format!("Restore {}", rev))?;
+            Ok(())
+        }
+        cli::Command::Encrypt => {
+            let passphrase = passphrase
+                .ok_or_else(|| eyre::eyre!("Pass the new passphrase with --passphrase-file"))?;
+            let _lock = instance::InstanceLock::acquire(&config.database())?;
+            let db = Db::open_or_new(config.database())?;
+            if db.is_encrypted()? {
+                return Err(eyre::eyre!(
+                    "The database is already encrypted, use `ritual rekey` to change its passphrase"
+                ));
+            }
+            if config.history {
+                warn!("Earlier commits of the database history stay unencrypted");
+            }
+            change_passphrase(&config, db, Some(&passphrase), "Encrypt database")
+        }
+        cli::Command::Decrypt => {
+            let _lock = instance::InstanceLock::acquire(&config.database())?;
+            let db = encrypted_db(&config, passphrase.as_ref())?;
+            change_passphrase(&config, db, None, "Decrypt database")
+        }
+        cli::Command::Rekey {
+            new_passphrase_file,
+        } => {
+            let new_passphrase = cli::read_passphrase(&new_passphrase_file)?;
+            let _lock = instance::InstanceLock::acquire(&config.database())?;
+            let db = encrypted_db(&config, passphrase.as_ref())?;
+            change_passphrase(
+                &config,
+                db,
+                Some(&new_passphrase),
+                "Change database passphrase",
+            )
+        }
+        cli::Command::Profile { command } => run_profile_command(command, config, config_path),
+    }
+}
+
+#[cfg(not(feature = "web"))]
+fn run_profile_command(
+    command: cli::ProfileCommand,
+    config: Config,
+    config_path: &std::path::Path,
+) -> Result<()> {
+    match command {
+        cli::ProfileCommand::List => {
+            let names = std::iter::once(config::DEFAULT_PROFILE)
+                .chain(config.profiles.keys().map(String::as_str));
+            let active = config.profile.as_deref().unwrap_or(config::DEFAULT_PROFILE);
+            for name in names {
+                let marker = if name == active { "*" } else { " " };
+                println!("{} {}", marker, name);
+            }
+            Ok(())
+        }
+        cli::ProfileCommand::Add {
+            name,
+            db,
+            sync_server,
+        } => {
+            let name = NonEmpty::new_validated(name.trim().to_string())
+                .map_err(|_| eyre::eyre!("Profile names cannot be empty"))?
+                .inner();
+            // Edit what is in the file, not the command line overrides
+            let mut file_config = Config::load(config_path)?;
+            if name == config::DEFAULT_PROFILE || file_config.profiles.contains_key(&name) {
+                return Err(eyre::eyre!("Profile {:?} already exists", name));
+            }
+            let mut profile = config::Profile::new(&name)?;
+            if let Some(db) = db {
+                profile.db_path = db;
+            }
+            profile.sync_server = sync_server;
+            info!(
+                "Adding profile {:?} with database {:?}",
+                name, profile.db_path
+            );
+            file_config.profiles.insert(name, profile);
+            file_config.save(config_path)
+        }
+        cli::ProfileCommand::Use { name } => {
+            let profile = (name != config::DEFAULT_PROFILE).then_some(name);
+            Config::save_profile(config_path, profile)
+        }
+    }
+}
+
+/// Unlocks `db` if it is encrypted
+#[cfg(not(feature = "web"))]
+fn unlocked(mut db: Db, passphrase: Option<&Passphrase>) -> Result<Db> {
+    if db.is_encrypted()? {
+        let passphrase = passphrase.ok_or_else(|| {
+            eyre::eyre!("The database is encrypted, pass its passphrase with --passphrase-file")
+        })?;
+        db.unlock(passphrase)?;
+    }
+    Ok(db)
+}
+
+#[cfg(not(feature = "web"))]
+fn encrypted_db(config: &Config, passphrase: Option<&Passphrase>) -> Result<Db> {
+    let db = unlocked(Db::open_or_new(config.database())?, passphrase)?;
+    if db.cipher().is_none() {
+        return Err(eyre::eyre!("The database is not encrypted"));
+    }
+    Ok(db)
+}
+
+/// Rewrites the database and its sync log encrypted with `passphrase`, or in
+/// plain text if there is none
+#[cfg(not(feature = "web"))]
+fn change_passphrase(
+    config: &Config,
+    mut db: Db,
+    passphrase: Option<&Passphrase>,
+    message: &str,
+) -> Result<()> {
+    let state = db.load()?;
+    let mut replica = storage::exists(&sync::log_path(&config.database()))
+        .then(|| sync::Replica::open(&config.database(), db.cipher().cloned()))
+        .transpose()?;
+    db.set_passphrase(passphrase)?;
+    db.save(&state)?;
+    if let Some(replica) = &mut replica {
+        replica.set_cipher(db.cipher().cloned());
+        replica.save()?;
+    }
+    if config.history {
+        history::History::open(&config.database())?.commit(message)?;
+    }
+    Ok(())
+}
+
+/// Syncs the database with other devices, through the running app if there is one
+#[cfg(not(feature = "web"))]
+fn sync_db(config: &Config, passphrase: Option<&Passphrase>) -> Result<()> {
+    let server = config
+        .sync_server()
+        .ok_or_else(|| eyre::eyre!("No sync server configured for this profile"))?;
+    #[cfg(unix)]
+    if let Some(mut client) = instance::Client::connect(&config.database())? {
+        info!("Asking the running app to sync");
+        return client.send(RitualCmd::Sync);
+    }
+    let _lock = instance::InstanceLock::acquire(&config.database())?;
+    let db = unlocked(Db::open_or_new(config.database())?, passphrase)?;
+    let mut state = db.load()?;
+    let mut replica = sync::Replica::open(&config.database(), db.cipher().cloned())?;
+    let applied = sync::sync(&mut replica, &server, &mut state, &config.calendar())?;
+    info!("Merged {} changes from other devices", applied);
+    db.save(&state)
+}
+
+/// Applies a command built from the current state, through the running app if
+/// there is one and directly to the database file otherwise
+#[cfg(not(feature = "web"))]
+fn edit_db(
+    config: &Config,
+    passphrase: Option<&Passphrase>,
+    cmd_for: impl FnOnce(&State, &config::Calendar) -> Result<RitualCmd>,
+) -> Result<()> {
+    let calendar = config.calendar();
+    #[cfg(unix)]
+    if let Some(mut client) = instance::Client::connect(&config.database())? {
+        let cmd = cmd_for(&client.state()?, &calendar)?;
+        info!("Forwarding {:?} to the running app", cmd);
+        client.send(cmd)?;
+        return client.send(RitualCmd::Save);
+    }
+    let _lock = instance::InstanceLock::acquire(&config.database())?;
+    let db = unlocked(Db::open_or_new(config.database())?, passphrase)?;
+    let mut state = db.load()?;
+    let cmd = cmd_for(&state, &calendar)?;
+    let change = cmd.describe(&state);
+    cmd.apply(&mut state, &calendar)?;
+    db.save(&state)?;
+    if config.history {
+        history::History::open(&config.database())?
+            .commit(&history::message(&Vec::from_iter(change)))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "web"))]
+fn cli_today<'a>(state: &'a State, calendar: &config::Calendar) -> Result<&'a types::Day> {
+    state
+        .day_on(calendar.today(), calendar)
+        .ok_or_else(|| eyre::eyre!("Today has not been added yet, run `ritual new-day`"))
+}
+
+/// Finds a habit by title or id, whether or not it is on today
+#[cfg(not(feature = "web"))]
+fn cli_habit(state: &State, habit: &str) -> Result<Uuid> {
+    state
+        .habits
+        .values()
+        .find(|h| h.id.to_string() == habit || h.title.eq_ignore_ascii_case(habit))
+        .map(|h| h.id)
+        .ok_or_else(|| eyre::eyre!("No habit {:?}", habit))
+}
+
+#[cfg(not(feature = "web"))]
+fn cli_set_status(
+    state: &State,
+    calendar: &config::Calendar,
+    habit: &str,
+    status: HabitStatus,
+) -> Result<RitualCmd> {
+    let day = cli_today(state, calendar)?;
+    let habit_ref = day
+        .habits
+        .values()
+        .find(|h| h.id.to_string() == habit || state.habit_name(h).eq_ignore_ascii_case(habit))
+        .ok_or_else(|| eyre::eyre!("No habit {:?} today", habit))?;
+    Ok(RitualCmd::HabitSetStatus {
+        day_id: day.id,
+        habit_id: habit_ref.id,
+        status,
+    })
+}
+
 #[component]
 fn App() -> Element {
+    let initial_config = use_context::<Config>();
+    let source = use_context_provider(|| Signal::new(consume_context::<ConfigSource>()));
+    let config = use_context_provider(|| Signal::new(initial_config));
+    // Only unlocks the database the app was started with
+    use_context_provider(|| Signal::new(consume_context::<Option<Passphrase>>()));
+    let locale = config.read().locale();
+    if i18n::locale() != locale {
+        i18n::set_locale(locale);
+    }
+    let database = config.read().database();
+    let database = database.display();
+    use_effect(move || {
+        let theme = config.read().theme;
+        document::eval(&format!(
+            "document.documentElement.dataset.theme = '{theme}';"
+        ));
+    });
+
+    let mut user_css = use_signal(String::new);
+    use_future(move || {
+        let path = source.read().user_css_path();
+        async move {
+            let read = |path: &std::path::Path| storage::read_to_string(path).unwrap_or_default();
+            user_css.set(read(&path));
+            // Hot reloading needs a filesystem to watch
+            #[cfg(not(feature = "web"))]
+            let mut watcher = match watch::FileWatcher::new(&path) {
+                Ok(watcher) => watcher,
+                Err(err) => {
+                    error!("Failed to watch user CSS {:?}: {}", path, err);
+                    return;
+                }
+            };
+            #[cfg(not(feature = "web"))]
+            while watcher.changed().await.is_some() {
+                info!("Reloading user CSS from {:?}", path);
+                user_css.set(read(&path));
+            }
+        }
+    });
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: NORMALIZE_CSS }
+        document::Link { rel: "stylesheet", href: REMOVE_DEFAULT_STYLES_CSS }
+        document::Link { rel: "stylesheet", href: MAIN_CSS }
+        style { "{user_css}" }
+        Workspace { key: "{database}" }
+    }
+}
+
+/// Saves the config a [`RitualCmd::SwitchProfile`] or [`RitualCmd::SwitchDatabase`]
+/// asks for and points the app at its database, which remounts the [`Workspace`].
+/// Nothing changes if another process holds that database.
+fn switch_database(
+    mut config: Signal<Config>,
+    mut source: Signal<ConfigSource>,
+    msg: RitualCmd,
+) -> Result<()> {
+    let path = source.read().path.clone();
+    let (new_config, overrides) = match msg {
+        RitualCmd::SwitchProfile { profile } => {
+            let mut new_config = Config::load(&path)?;
+            if let Some(name) = &profile {
+                if !new_config.profiles.contains_key(name) {
+                    return Err(eyre::eyre!("Unknown profile {:?}", name));
+                }
+            }
+            new_config.profile = profile;
+            // The database picked on the command line is left behind too
+            (new_config, Default::default())
+        }
+        RitualCmd::SwitchDatabase { config } => (*config, source.read().overrides.clone()),
+        _ => return Ok(()),
+    };
+    let effective = overrides.apply(new_config.clone());
+    let db_path = effective.database();
+    #[cfg(not(feature = "web"))]
+    if db_path != config.read().database() {
+        instance::InstanceLock::acquire(&db_path)?;
+    }
+    info!("Switching to database {:?}", db_path);
+    new_config.save(&path)?;
+    source.write().overrides = overrides;
+    config.set(effective);
+    Ok(())
+}
+
+/// Everything tied to the database of the active profile, remounted with a fresh
+/// state when switching to another one
+#[component]
+fn Workspace() -> Element {
+    // Held while the database is in use, so CLI commands go through the app.
+    // Switching databases checks it first, so this only fails if another process
+    // took it in between.
+    #[cfg(not(feature = "web"))]
+    let lock = use_hook(|| {
+        let db_path = consume_context::<Signal<Config>>().read().database();
+        std::rc::Rc::new(instance::InstanceLock::acquire(&db_path))
+    });
+    #[cfg(not(feature = "web"))]
+    if let Err(err) = &*lock {
+        error!("{}", err);
+        return rsx! {
+            p { class: "db-failed", {t!("error-db-failed", error = err.to_string())} }
+        };
+    }
+    rsx! {
+        Session {}
+    }
+}
+
+/// Shows why the database could not be opened, after which moving over to another
+/// one is all the workspace still does
+async fn failed(
+    err: eyre::Report,
+    mut lock_status: Signal<LockStatus>,
+    config: Signal<Config>,
+    source: Signal<ConfigSource>,
+    rx: &mut UnboundedReceiver<RitualCmd>,
+) {
+    error!("Failed to open db: {}", err);
+    lock_status.set(LockStatus::Failed {
+        error: err.to_string(),
+    });
+    while let Some(msg) = rx.next().await {
+        if let Err(err) = switch_database(config, source, msg) {
+            error!("Failed to switch database: {}", err);
+        }
+    }
+}
+
+/// The state of the database and the tasks that keep it in sync with the file,
+/// other processes and devices
+#[component]
+fn Session() -> Element {
+    let config = use_context::<Signal<Config>>();
     let mut db_state = use_context_provider(|| Signal::new(State::default()));
+    let mut sync_status = use_context_provider(|| Signal::new(SyncStatus::default()));
+    let mut lock_status = use_context_provider(|| Signal::new(LockStatus::default()));
+    let strengths =
+        use_memo(move || strength::strengths(&db_state.read(), &config.read().calendar()));
+    use_context_provider(|| strengths);
+    let mut initial_passphrase = use_context::<Signal<Option<Passphrase>>>();
+    let source = use_context::<Signal<ConfigSource>>();
 
-    use_coroutine::<RitualCmd, _, _>(move |mut rx| async move {
-        let db: Db = Db::open_or_new("/tmp/db.json".into()).expect("Failed to open db");
-        // load db contents into db_state
-        db_state.with_mut(|state| {
-            *state = db.load().unwrap_or_else(|err| {
-                error!("Failed to load db: {}", err);
-                std::process::exit(1);
+    let cmd = use_coroutine::<RitualCmd, _, _>(move |mut rx| async move {
+        let db_path = config.read().database();
+        let opened = Db::open_or_new(db_path.clone()).and_then(|db| Ok((db.is_locked()?, db)));
+        let (locked, mut db) = match opened {
+            Ok(opened) => opened,
+            Err(err) => return failed(err, lock_status, config, source, &mut rx).await,
+        };
+        // Commands that come in before the db is unlocked, handled once it is
+        let mut queued = Vec::<RitualCmd>::new();
+        if locked {
+            let mut passphrase = initial_passphrase.take();
+            lock_status.set(LockStatus::Locked { failed: false });
+            loop {
+                if let Some(passphrase) = passphrase.take() {
+                    match db.unlock(&passphrase) {
+                        Ok(()) => break,
+                        Err(err) => {
+                            warn!("Failed to unlock db: {}", err);
+                            lock_status.set(LockStatus::Locked { failed: true });
+                        }
+                    }
+                }
+                match rx.next().await {
+                    Some(RitualCmd::Unlock {
+                        passphrase: entered,
+                    }) => passphrase = Some(entered),
+                    Some(
+                        msg @ (RitualCmd::SwitchProfile { .. } | RitualCmd::SwitchDatabase { .. }),
+                    ) => {
+                        if let Err(err) = switch_database(config, source, msg) {
+                            error!("Failed to switch database: {}", err);
+                        }
+                    }
+                    Some(msg) => {
+                        trace!("Queueing {:?} until the db is unlocked", msg);
+                        queued.push(msg);
+                    }
+                    None => return,
+                }
+            }
+        }
+        // Commit messages, committed one after another off the UI thread since git
+        // can take a while
+        #[cfg(not(feature = "web"))]
+        let history = config
+            .read()
+            .history
+            .then(|| history::History::open(&db_path))
+            .transpose()
+            .unwrap_or_else(|err| {
+                error!("Failed to open history: {}", err);
+                None
+            })
+            .map(|history| {
+                let (commits, mut pending) = tokio::sync::mpsc::unbounded_channel::<String>();
+                tokio::spawn(async move {
+                    while let Some(message) = pending.recv().await {
+                        let history = history.clone();
+                        match tokio::task::spawn_blocking(move || history.commit(&message)).await {
+                            Ok(Ok(_)) => {}
+                            Ok(Err(err)) => error!("Failed to commit history: {}", err),
+                            Err(err) => error!("History task failed: {}", err),
+                        }
+                    }
+                });
+                commits
             });
-        });
+        // Descriptions of the changes since the last save, for the history
+        let mut changes = Vec::<String>::new();
+        // load db contents into db_state
+        match db.load() {
+            Ok(state) => db_state.set(state),
+            Err(err) => return failed(err, lock_status, config, source, &mut rx).await,
+        }
+        lock_status.set(LockStatus::Unlocked);
+        let mut rx = futures_util::stream::iter(queued).chain(rx);
+
+        let db = &db;
+        #[cfg(not(feature = "web"))]
+        let history = &history;
+        // Unless `force` is set, refuses to write over changes another program made
+        // to the file since it was last loaded, raising a conflict instead
+        let mut save = move |changes: &mut Vec<String>, force: bool| -> Result<()> {
+            if !force {
+                if sync_status.read().conflict.is_some() {
+                    return Err(eyre::eyre!(
+                        "The database changed externally, pick which version to keep first"
+                    ));
+                }
+                if let Some(theirs) = db.load_if_changed()? {
+                    if !sync_status.read().dirty {
+                        db_state.set(theirs);
+                        return Ok(());
+                    }
+                    sync_status.write().conflict = Some(theirs);
+                    return Err(eyre::eyre!(
+                        "The database changed externally while there were unsaved changes"
+                    ));
+                }
+            }
+            db.save(&db_state.read())?;
+            sync_status.set(SyncStatus::default());
+            #[cfg(not(feature = "web"))]
+            if let Some(history) = history {
+                let _ = history.send(history::message(changes));
+            }
+            changes.clear();
+            Ok(())
+        };
 
         while let Some(msg) = rx.next().await {
             match msg {
-                RitualCmd::NewDay => {
-                    let date: DateTime = chrono::Utc::now();
-                    info!("Adding new day for date: {}", date);
-                    db_state.with_mut(|state| {
-                        state.add_day(date).unwrap_or_else(|err| {
-                            error!("Failed to add new day: {}", err);
-                        });
-                    });
+                RitualCmd::Save => {
+                    if let Err(err) = save(&mut changes, false) {
+                        error!("Failed to save db: {}", err);
+                    }
+                }
+                RitualCmd::KeepLocal => {
+                    if let Err(err) = save(&mut changes, true) {
+                        error!("Failed to save db: {}", err);
+                    }
                 }
-                RitualCmd::AddHabitToDay { title, day_id } => {
-                    info!("Adding habit to day: {}", day_id);
-                    let title = NonEmpty::new_validated(title);
-                    let title = match title {
-                        Ok(title) => title,
-                        Err(e) => {
-                            error!("Invalid habit title {e}");
+                msg @ (RitualCmd::SwitchProfile { .. } | RitualCmd::SwitchDatabase { .. }) => {
+                    if sync_status.read().conflict.is_some() {
+                        warn!("Not switching databases until the database conflict is resolved");
+                        continue;
+                    }
+                    // The workspace and its unsaved changes go away with the switch
+                    if sync_status.read().dirty {
+                        if let Err(err) = save(&mut changes, false) {
+                            error!("Failed to save db: {}", err);
                             continue;
                         }
-                    };
+                    }
+                    if let Err(err) = switch_database(config, source, msg) {
+                        error!("Failed to switch database: {}", err);
+                    }
+                }
+                RitualCmd::Reload => match db.load_if_changed() {
+                    Ok(None) => {}
+                    Ok(Some(theirs)) if sync_status.read().dirty => {
+                        warn!("Database changed externally while there are unsaved changes");
+                        sync_status.write().conflict = Some(theirs);
+                    }
+                    Ok(Some(theirs)) => db_state.set(theirs),
+                    Err(err) => error!("Failed to reload db: {}", err),
+                },
+                RitualCmd::Sync => {
+                    #[cfg(not(feature = "web"))]
+                    {
+                        let Some(server) = config.read().sync_server() else {
+                            warn!("No sync server configured");
+                            continue;
+                        };
+                        if sync_status.read().conflict.is_some() {
+                            warn!("Not syncing until the database conflict is resolved");
+                            continue;
+                        }
+                        let db_path = db_path.clone();
+                        let cipher = db.cipher().cloned();
+                        let mut state = db_state.read().clone();
+                        let calendar = config.read().calendar();
+                        let synced = tokio::task::spawn_blocking(move || {
+                            let mut replica = sync::Replica::open(&db_path, cipher)?;
+                            let applied = sync::sync(&mut replica, &server, &mut state, &calendar)?;
+                            Ok::<_, eyre::Report>((applied, state))
+                        })
+                        .await;
+                        match synced {
+                            Ok(Ok((0, _))) => info!("Already up to date"),
+                            Ok(Ok((applied, state))) => {
+                                info!("Merged {} changes from other devices", applied);
+                                changes
+                                    .push(format!("Merge {} changes from other devices", applied));
+                                db_state.set(state);
+                                sync_status.write().dirty = true;
+                                if let Err(err) = save(&mut changes, false) {
+                                    error!("Failed to save db: {}", err);
+                                }
+                            }
+                            Ok(Err(err)) => error!("Failed to sync: {}", err),
+                            Err(err) => error!("Sync task failed: {}", err),
+                        }
+                    }
+                    #[cfg(feature = "web")]
+                    warn!("Syncing is not available in the browser");
+                }
+                msg => {
+                    let calendar = config.read().calendar();
+                    let change = msg.describe(&db_state.read());
+                    match db_state.with_mut(|state| msg.apply(state, &calendar)) {
+                        Ok(()) => {
+                            sync_status.write().dirty = true;
+                            changes.extend(change);
+                        }
+                        Err(err) => error!("{}", err),
+                    }
+                }
+            }
+        }
+    });
 
-                    db_state.with_mut(|state| {
-                        state.add_habit_to_day(title, day_id).unwrap_or_else(|err| {
-                            error!("Failed to add habit to day {}: {}", day_id, err);
-                        });
-                    });
+    #[cfg(not(feature = "web"))]
+    use_future(move || async move {
+        let db_path = config.read().database();
+        let mut watcher = match watch::FileWatcher::new(&db_path) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                error!("Failed to watch db {:?}: {}", db_path, err);
+                return;
+            }
+        };
+        while watcher.changed().await.is_some() {
+            cmd.send(RitualCmd::Reload);
+        }
+    });
+
+    use_future(move || async move {
+        let mut scheduler = Scheduler::new(reminders::platform_notifier());
+        loop {
+            utils::sleep(reminders::TICK_INTERVAL).await;
+            let calendar = config.read().calendar();
+            scheduler.tick(&db_state.read(), chrono::Utc::now(), &calendar);
+        }
+    });
+
+    #[cfg(all(unix, not(feature = "web")))]
+    use_future(move || async move {
+        let db_path = config.read().database();
+        let mut incoming = match instance::listen(&db_path) {
+            Ok(incoming) => incoming,
+            Err(err) => {
+                error!("Failed to listen for commands: {}", err);
+                return;
+            }
+        };
+        while let Some(request) = incoming.recv().await {
+            // The state is empty until the db is unlocked, and edits to it would be
+            // lost
+            let unlocked = *lock_status.read() == LockStatus::Unlocked;
+            let refused = || "The database is locked, unlock it in the app first".to_string();
+            match request {
+                instance::IpcRequest::Cmd { cmd: msg, reply } => {
+                    if unlocked {
+                        cmd.send(msg);
+                    }
+                    let _ = reply.send(if unlocked { Ok(()) } else { Err(refused()) });
                 }
-                RitualCmd::HabitSetDone {
-                    day_id,
-                    habit_id,
-                    done,
-                } => {
-                    info!(
-                        "Setting habit {} for day {} to done: {}",
-                        habit_id, day_id, done
-                    );
-                    db_state.with_mut(|state| {
-                        state
-                            .set_habit_done(day_id, habit_id, done)
-                            .unwrap_or_else(|err| {
-                                error!(
-                                    "Failed to set habit {} for day {} to done: {}",
-                                    habit_id, day_id, err
-                                );
-                            });
-                    });
+                instance::IpcRequest::State { reply } => {
+                    let state = unlocked.then(|| db_state.read().clone());
+                    let _ = reply.send(state.ok_or_else(refused));
                 }
-                RitualCmd::Save => db.save(&db_state.read()).unwrap_or_else(|err| {
-                    error!("Failed to save db: {}", err);
-                }),
+            }
+        }
+    });
+
+    // Whether the API may answer, kept in step with the lock status since the
+    // server runs outside of the UI
+    #[cfg(not(feature = "web"))]
+    let api_unlocked = use_hook(|| std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)));
+    #[cfg(not(feature = "web"))]
+    use_effect({
+        let api_unlocked = api_unlocked.clone();
+        move || {
+            let unlocked = *lock_status.read() == LockStatus::Unlocked;
+            api_unlocked.store(unlocked, std::sync::atomic::Ordering::Relaxed);
+        }
+    });
+    #[cfg(not(feature = "web"))]
+    use_future(move || {
+        let unlocked = api_unlocked.clone();
+        async move {
+            let api_config = config.read().api.clone();
+            if !api_config.enabled {
+                return;
+            }
+            let (requests, mut incoming) = tokio::sync::mpsc::unbounded_channel();
+            tokio::spawn(async move {
+                if let Err(err) = api::serve(api_config, requests, unlocked).await {
+                    error!("API server stopped: {}", err);
+                }
+            });
+            while let Some(request) = incoming.recv().await {
+                let calendar = config.read().calendar();
+                api::handle(request, &db_state.read(), &calendar, |c| cmd.send(c));
+            }
+        }
+    });
+
+    #[cfg(not(feature = "web"))]
+    use_future(move || async move {
+        loop {
+            let (server, interval) = {
+                let config = config.read();
+                (config.sync_server(), config.sync.interval)
+            };
+            if server.is_none() || interval == 0 {
+                utils::sleep(std::time::Duration::from_secs(1)).await;
+                continue;
+            }
+            utils::sleep(std::time::Duration::from_secs(interval)).await;
+            cmd.send(RitualCmd::Sync);
+        }
+    });
+
+    use_future(move || async move {
+        loop {
+            let interval = config.read().autosave_interval;
+            if interval == 0 {
+                utils::sleep(std::time::Duration::from_secs(1)).await;
+                continue;
+            }
+            utils::sleep(std::time::Duration::from_secs(interval)).await;
+            // Saving over a conflict would silently throw away the external changes
+            let status = sync_status.read();
+            if status.dirty && status.conflict.is_none() {
+                cmd.send(RitualCmd::Save);
             }
         }
     });
 
     rsx! {
-        document::Link { rel: "stylesheet", href: NORMALIZE_CSS }
-        document::Link { rel: "stylesheet", href: REMOVE_DEFAULT_STYLES_CSS }
-        document::Link { rel: "stylesheet", href: MAIN_CSS }
         Router::<Route> {}
     }
 }
 
 #[component]
-fn Home() -> Element {
+fn Layout() -> Element {
     let cmd = use_coroutine_handle::<RitualCmd>();
-    let state = use_context::<Signal<State>>();
-    let days = state.read().days.clone();
-    let sorted_days = days.values().sorted_by_key(|d| d.date).collect::<Vec<_>>();
-    // check if today is already present (only check date, not hours)
-    let add_days_enabled = if sorted_days
-        .last()
-        .is_none_or(|d| d.date.date_naive() != chrono::Utc::now().date_naive())
-    {
-        "enabled"
-    } else {
-        "disabled"
+    let config = use_context::<Signal<Config>>();
+    let lock_status = use_context::<Signal<LockStatus>>();
+    let navigator = use_navigator();
+    // Remount the routes when the language changes so all text is translated again
+    let locale = config.read().locale();
+
+    #[cfg(feature = "desktop")]
+    let title_bar = rsx! {
+        components::title_bar::TitleBar { title: "ritual" }
     };
+    #[cfg(not(feature = "desktop"))]
+    let title_bar = rsx! {};
+
+    let onkeydown = move |e: KeyboardEvent| {
+        let shortcuts = config.read().shortcuts.clone();
+        let pressed = |binding: &str| {
+            binding
+                .parse::<Shortcut>()
+                .is_ok_and(|shortcut| shortcut.matches(&e))
+        };
+        if pressed(&shortcuts.new_day) {
+            cmd.send(RitualCmd::NewDay);
+        } else if pressed(&shortcuts.save) {
+            cmd.send(RitualCmd::Save);
+        } else if pressed(&shortcuts.home) {
+            navigator.push(Route::Home {});
+        } else if pressed(&shortcuts.settings) {
+            navigator.push(Route::Settings {});
+        } else {
+            return;
+        }
+        e.prevent_default();
+    };
+
     rsx! {
         div {
-            class: "main",
-            h1 { "Ritual" },
+            class: "layout",
+            tabindex: -1,
+            onmounted: move |e| async move {
+                let _ = e.set_focus(true).await;
+            },
+            onkeydown,
+            {title_bar}
             div {
-                class: "days",
-                for day in sorted_days {
-                    Day { day: day.clone() }
+                key: "{locale}",
+                class: "content",
+                nav {
+                    class: "nav",
+                    Link { to: Route::Home {}, {t!("nav-days")} }
+                    Link { to: Route::Week {}, {t!("nav-week")} }
+                    Link { to: Route::Month {}, {t!("nav-month")} }
+                    Link { to: Route::Search {}, {t!("nav-search")} }
+                    Link { to: Route::Stats {}, {t!("nav-stats")} }
+                    Link { to: Route::Settings {}, {t!("nav-settings")} }
+                    ProfileSwitcher {}
                 }
-                Button {
-                    disabled: add_days_enabled == "disabled",
-                    class: add_days_enabled,
-                    onclick: move |_| {
-                        cmd.send(RitualCmd::NewDay);
-                },
-                    "Add Day"
+                match &*lock_status.read() {
+                    LockStatus::Loading => rsx! {},
+                    LockStatus::Failed { error } => rsx! {
+                        p { class: "db-failed", {t!("error-db-failed", error = error.clone())} }
+                    },
+                    LockStatus::Unlocked => rsx! {
+                        ConflictPrompt {}
+                        PauseBanner {}
+                        Outlet::<Route> {}
+                    },
+                    LockStatus::Locked { .. } => rsx! {
+                        Unlock {}
+                    },
                 }
             }
+        }
+    }
+}
+
+/// Moves over to another profile, only shown once there is more than one
+#[component]
+fn ProfileSwitcher() -> Element {
+    let cmd = use_coroutine_handle::<RitualCmd>();
+    let config = use_context::<Signal<Config>>();
+    let names = config.read().profiles.keys().cloned().collect::<Vec<_>>();
+    if names.is_empty() {
+        return rsx! {};
+    }
+    let active = config
+        .read()
+        .profile
+        .clone()
+        .unwrap_or_else(|| config::DEFAULT_PROFILE.to_string());
+
+    rsx! {
+        select {
+            class: "profiles",
+            onchange: move |e| {
+                let name = e.value();
+                let profile = (name != config::DEFAULT_PROFILE).then_some(name);
+                cmd.send(RitualCmd::SwitchProfile { profile });
+            },
+            option {
+                value: config::DEFAULT_PROFILE,
+                selected: active == config::DEFAULT_PROFILE,
+                {t!("profile-default")}
+            }
+            for name in names {
+                option { value: "{name}", selected: name == active, "{name}" }
+            }
+        }
+    }
+}
+
+/// Asks for the passphrase of an encrypted database
+#[component]
+fn Unlock() -> Element {
+    let cmd = use_coroutine_handle::<RitualCmd>();
+    let lock_status = use_context::<Signal<LockStatus>>();
+    let mut passphrase = use_signal(String::new);
+    let failed = *lock_status.read() == LockStatus::Locked { failed: true };
+
+    rsx! {
+        form {
+            class: "main unlock",
+            onsubmit: move |e| {
+                e.prevent_default();
+                cmd.send(RitualCmd::Unlock {
+                    passphrase: Passphrase::new(passphrase.take()),
+                });
+            },
+            h1 { {t!("unlock-title")} }
+            input {
+                r#type: "password",
+                autofocus: true,
+                placeholder: t!("unlock-placeholder"),
+                value: "{passphrase}",
+                oninput: move |e| passphrase.set(e.data.value())
+            }
+            Button { class: "enabled", r#type: "submit", {t!("unlock")} }
+            if failed {
+                span { class: "error", {t!("unlock-failed")} }
+            }
+        }
+    }
+}
+
+/// Asks which version to keep when the database file changed while there were
+/// unsaved changes
+#[component]
+fn ConflictPrompt() -> Element {
+    let cmd = use_coroutine_handle::<RitualCmd>();
+    let mut db_state = use_context::<Signal<State>>();
+    let mut sync_status = use_context::<Signal<SyncStatus>>();
+    if sync_status.read().conflict.is_none() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div {
+            class: "conflict",
+            span { {t!("conflict-message")} }
             Button {
+                class: "enabled",
+                onclick: move |_| cmd.send(RitualCmd::KeepLocal),
+                {t!("conflict-keep-local")}
+            }
+            Button {
+                class: "enabled",
                 onclick: move |_| {
-                    cmd.send(RitualCmd::Save);
+                    let theirs = sync_status.write().conflict.take();
+                    if let Some(theirs) = theirs {
+                        db_state.set(theirs);
+                        sync_status.write().dirty = false;
+                    }
                 },
-                "Save"
+                {t!("conflict-use-external")}
             }
-            components::emoji_picker::EmojiPicker {
-                on_select: move |_| {
-                        todo!()
+        }
+    }
+}
+
+/// Shows the pauses that are on today, each with a way to end it early
+#[component]
+fn PauseBanner() -> Element {
+    let cmd = use_coroutine_handle::<RitualCmd>();
+    let state = use_context::<Signal<State>>();
+    let config = use_context::<Signal<Config>>();
+    let today = config.read().calendar().today();
+    let pauses = state
+        .read()
+        .pauses
+        .values()
+        .filter(|pause| pause.is_on(today))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    rsx! {
+        for pause in pauses {
+            div {
+                key: "{pause.id}",
+                class: "pause",
+                span {
+                    {
+                        let end = i18n::fmt_nice_date(pause.end, today, i18n::locale());
+                        match pause.habit_id.and_then(|id| state.read().habits.get(&id).cloned()) {
+                            Some(habit) => t!("pause-habit", habit = habit.title, end = end),
+                            None => t!("pause-all", end = end),
+                        }
+                    }
+                }
+                Button {
+                    class: "enabled",
+                    onclick: move |_| cmd.send(RitualCmd::EndPause { pause_id: pause.id }),
+                    {t!("pause-resume")}
                 }
             }
         }
     }
 }
 
-use chrono::Utc;
-fn fmt_nice_date(date: DateTime) -> String {
-    fn get_day_suffix(day: u32) -> &'static str {
-        match day {
-            11 | 12 | 13 => "th",
-            _ => match day % 10 {
-                1 => "st",
-                2 => "nd",
-                3 => "rd",
-                _ => "th",
+/// Pauses one habit or all of them from today through a chosen day
+#[component]
+fn PauseForm() -> Element {
+    let cmd = use_coroutine_handle::<RitualCmd>();
+    let state = use_context::<Signal<State>>();
+    let config = use_context::<Signal<Config>>();
+    let mut show_form = use_signal(|| false);
+    let mut end = use_signal(|| None::<NaiveDate>);
+    let mut habit_id = use_signal(|| None::<Uuid>);
+    let today = config.read().calendar().today();
+    let habits = state
+        .read()
+        .habits
+        .values()
+        .filter(|habit| !habit.archived)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if !show_form() {
+        return rsx! {
+            Button {
+                class: "enabled",
+                onclick: move |_| show_form.set(true),
+                {t!("pause")}
+            }
+        };
+    }
+
+    rsx! {
+        form {
+            class: "pause-form",
+            onsubmit: move |e| {
+                e.prevent_default();
+                if let Some(end) = end() {
+                    cmd.send(RitualCmd::AddPause { start: today, end, habit_id: habit_id() });
+                    show_form.set(false);
+                }
             },
+            select {
+                onchange: move |e| habit_id.set(e.value().parse().ok()),
+                option { value: "", {t!("pause-all-habits")} }
+                for habit in habits {
+                    option { value: "{habit.id}", "{habit.title}" }
+                }
+            }
+            label {
+                {t!("pause-until")}
+                input {
+                    r#type: "date",
+                    min: "{today}",
+                    oninput: move |e| end.set(e.value().parse().ok()),
+                }
+            }
+            Button {
+                class: if end().is_some() { "submit enabled" } else { "submit disabled" },
+                disabled: end().is_none(),
+                r#type: "submit",
+                Icon { icon: IoCheckmarkOutline }
+            }
+            Button {
+                class: "cancel",
+                onclick: move |_| show_form.set(false),
+                Icon { icon: IoCloseOutline }
+            }
         }
     }
-    fn get_month_name(month: u32) -> &'static str {
-        match month {
-            1 => "January",
-            2 => "February",
-            3 => "March",
-            4 => "April",
-            5 => "May",
-            6 => "June",
-            7 => "July",
-            8 => "August",
-            9 => "September",
-            10 => "October",
-            11 => "November",
-            12 => "December",
-            _ => "",
-        }
-    }
-    let today = Utc::now().date_naive();
-    let input_date = date.date_naive();
-    let duration = today.signed_duration_since(input_date);
-
-    if duration.num_days() == 0 {
-        "Today".to_string()
-    } else if duration.num_days() == 1 {
-        "Yesterday".to_string()
-    } else if duration.num_days() < 7 && date.weekday() == today.weekday().pred() {
-        format!("{}", date.weekday())
-    } else if date.month() == today.month() && date.year() == today.year() {
-        format!("{}{}", date.day(), get_day_suffix(date.day()))
+}
+
+/// How many of the latest days Home shows at first, and how many more it loads
+/// each time the list is scrolled to the end
+const DAYS_PER_PAGE: usize = 14;
+
+#[component]
+fn Home() -> Element {
+    let cmd = use_coroutine_handle::<RitualCmd>();
+    let state = use_context::<Signal<State>>();
+    let config = use_context::<Signal<Config>>();
+    let calendar = config.read().calendar();
+    let today = calendar.today();
+    // Where the loaded days start, the first page ending at the latest day however
+    // long ago it was until more are loaded
+    let mut from = use_signal(|| None::<NaiveDate>);
+    let start = from()
+        .or_else(|| {
+            state
+                .read()
+                .page_start(today.succ_opt()?, DAYS_PER_PAGE, &calendar)
+        })
+        .unwrap_or(today);
+    // Only the ids of the loaded days, newest first, each one is read as it is shown
+    let day_ids = state
+        .read()
+        .days_in(start.., &calendar)
+        .into_iter()
+        .rev()
+        .map(|day| day.id.to_string())
+        .collect::<Vec<_>>();
+    let add_days_enabled = if state.read().day_on(today, &calendar).is_none() {
+        "enabled"
     } else {
-        format!(
-            "{} of {}, {}",
-            date.day(),
-            get_month_name(date.month()),
-            date.year()
-        )
+        "disabled"
+    };
+    rsx! {
+        div {
+            class: "main",
+            h1 { {t!("app-title")} },
+            Button {
+                disabled: add_days_enabled == "disabled",
+                class: add_days_enabled,
+                onclick: move |_| {
+                    cmd.send(RitualCmd::NewDay);
+                },
+                {t!("add-day")}
+            }
+            components::virtual_list::VirtualList {
+                class: "days",
+                keys: day_ids,
+                estimated_height: 180.0,
+                render: move |day_id: String| {
+                    let day = Uuid::parse_str(&day_id)
+                        .ok()
+                        .and_then(|day_id| state.read().days.get(&day_id).cloned());
+                    rsx! {
+                        if let Some(day) = day {
+                            Day { day }
+                        }
+                    }
+                },
+                on_end_reached: move |_| {
+                    let calendar = config.read().calendar();
+                    let next = state.read().page_start(start, DAYS_PER_PAGE, &calendar);
+                    if let Some(next) = next {
+                        from.set(Some(next));
+                    }
+                },
+            }
+            PauseForm {}
+            Button {
+                onclick: move |_| {
+                    cmd.send(RitualCmd::Save);
+                },
+                {t!("save")}
+            }
+            components::emoji_picker::EmojiPicker {
+                on_select: move |_| {}
+            }
+        }
     }
 }
 
 #[component]
 fn Day(day: types::Day) -> Element {
+    let config = use_context::<Signal<Config>>();
+    let calendar = config.read().calendar();
     rsx! {
         div {
             class: "day",
             h3 {
                 class: "date",
-                "{fmt_nice_date(day.date)}"
+                {i18n::fmt_nice_date(calendar.date_of(day.date), calendar.today(), i18n::locale())}
             }
             div {
                 class: "habits",
@@ -242,6 +1451,69 @@ fn Day(day: types::Day) -> Element {
                 }
             }
             NewHabitForm { day_id: day.id }
+            DayJournal { day_id: day.id, journal: day.journal, mood: day.mood }
+        }
+    }
+}
+
+/// Mood rating and journal entry of a day. The entry is shown as markdown until
+/// it is edited.
+#[component]
+fn DayJournal(day_id: Uuid, journal: Option<String>, mood: Option<u8>) -> Element {
+    let cmd = use_coroutine_handle::<RitualCmd>();
+    let mut editing = use_signal(|| false);
+    let mut draft = use_signal(String::new);
+    let entry = journal.as_deref().map(markdown::to_html);
+    let edit = move |_| {
+        draft.set(journal.clone().unwrap_or_default());
+        editing.set(true);
+    };
+
+    rsx! {
+        div {
+            class: "journal",
+            div {
+                class: "mood",
+                span { {t!("mood")} }
+                for level in types::Day::MOODS {
+                    Button {
+                        class: if mood == Some(level) { "enabled selected" } else { "enabled" },
+                        onclick: move |_| {
+                            let mood = (mood != Some(level)).then_some(level);
+                            cmd.send(RitualCmd::SetDayMood { day_id, mood });
+                        },
+                        "{level}"
+                    }
+                }
+            }
+            if editing() {
+                form {
+                    class: "journal-form",
+                    onsubmit: move |e| {
+                        e.prevent_default();
+                        cmd.send(RitualCmd::SetDayJournal { day_id, journal: draft() });
+                        editing.set(false);
+                    },
+                    textarea {
+                        placeholder: t!("journal-placeholder"),
+                        value: "{draft}",
+                        oninput: move |e| draft.set(e.data.value()),
+                    }
+                    Button { class: "submit", r#type: "submit",
+                        Icon { icon: IoCheckmarkOutline }
+                    }
+                    Button {
+                        class: "cancel",
+                        onclick: move |_| editing.set(false),
+                        Icon { icon: IoCloseOutline }
+                    }
+                }
+            } else if let Some(entry) = entry {
+                div { class: "entry", dangerous_inner_html: entry }
+                Button { class: "enabled", onclick: edit, {t!("journal-edit")} }
+            } else {
+                Button { class: "enabled", onclick: edit, {t!("journal-add")} }
+            }
         }
     }
 }
@@ -277,7 +1549,7 @@ fn NewHabitForm(day_id: Uuid) -> Element {
             },
             input {
                 r#type: "text",
-                placeholder: "New Habit",
+                placeholder: t!("new-habit-placeholder"),
                 value: "{title}",
                 oninput: move |e| title.set(e.data.value())
             }
@@ -306,21 +1578,65 @@ fn NewHabitForm(day_id: Uuid) -> Element {
 #[component]
 fn Habit(day_id: Uuid, habit: types::HabitRef) -> Element {
     let cmd = use_coroutine_handle::<RitualCmd>();
+    let state = use_context::<Signal<State>>();
+    let mut show_options = use_signal(|| false);
+    let strength = use_context::<Memo<std::collections::HashMap<Uuid, f64>>>()
+        .read()
+        .get(&habit.id)
+        .copied()
+        .unwrap_or_default();
+    let name = state.read().habit_name(&habit).to_string();
+    let accent = state
+        .read()
+        .habits
+        .get(&habit.id)
+        .and_then(|h| h.color.clone())
+        .map(|color| format!("--accent: {color}"))
+        .unwrap_or_default();
     rsx! {
         div {
             class: "habit",
-            div { span {
-                class: "name",
-                "{habit.name}" }
+            style: accent,
+            div {
+                Link {
+                    class: "name",
+                    to: Route::HabitDetail { id: habit.id },
+                    "{name}"
+                }
+                span {
+                    class: "strength",
+                    title: t!("habit-strength"),
+                    {format!("{:.0}%", strength * 100.0)}
+                }
+                Button {
+                    class: "habit-options-toggle",
+                    onclick: move |_| show_options.toggle(),
+                    Icon {
+                        icon: IoEllipsisHorizontal,
+                    }
+                }
             }
-            input {
-                r#type: "checkbox",
-                checked: habit.done,
-                onchange: move |e| {
-                    cmd.send(RitualCmd::HabitSetDone {
+            if show_options() {
+                HabitOptions { habit_id: habit.id }
+                input {
+                    class: "note",
+                    r#type: "text",
+                    placeholder: t!("habit-note-placeholder"),
+                    value: habit.note.clone().unwrap_or_default(),
+                    onchange: move |e| {
+                        cmd.send(RitualCmd::SetHabitNote { day_id, habit_id: habit.id, note: e.data.value() });
+                    }
+                }
+            } else if let Some(note) = &habit.note {
+                span { class: "note", "{note}" }
+            }
+            HabitStatusButton {
+                status: habit.status,
+                onclick: move |_| {
+                    cmd.send(RitualCmd::HabitSetStatus {
                         day_id,
                         habit_id: habit.id,
-                        done: e.data.value() == "true",
+                        status: habit.status.next(),
                     });
                 }
             }
@@ -328,6 +1644,93 @@ fn Habit(day_id: Uuid, habit: types::HabitRef) -> Element {
     }
 }
 
+/// Shows how a habit went and moves on to the next status when clicked
+#[component]
+fn HabitStatusButton(status: HabitStatus, onclick: EventHandler<MouseEvent>) -> Element {
+    let label = t!(&format!("status-{}", status));
+    rsx! {
+        button {
+            class: "status {status}",
+            title: "{label}",
+            aria_label: "{label}",
+            onclick: move |e| onclick.call(e),
+            match status {
+                HabitStatus::Pending => rsx! {},
+                HabitStatus::Done => rsx! { Icon { icon: IoCheckmarkOutline } },
+                HabitStatus::Skipped => rsx! { Icon { icon: IoRemoveOutline } },
+                HabitStatus::Failed => rsx! { Icon { icon: IoCloseOutline } },
+            }
+        }
+    }
+}
+
+#[component]
+fn HabitOptions(habit_id: Uuid) -> Element {
+    let cmd = use_coroutine_handle::<RitualCmd>();
+    let state = use_context::<Signal<State>>();
+    let config = use_context::<Signal<Config>>();
+    let Some(habit) = state.read().habits.get(&habit_id).cloned() else {
+        return rsx! {};
+    };
+    let reminder = habit
+        .reminder
+        .map(|t| t.format("%H:%M").to_string())
+        .unwrap_or_default();
+    let week_start = config.read().week_start;
+    let weekdays = std::iter::successors(Some(week_start), |d| Some(d.succ())).take(7);
+    rsx! {
+        div {
+            class: "habit-options",
+            div {
+                class: "schedule",
+                for weekday in weekdays {
+                    Button {
+                        class: if habit.schedule.weekdays.contains(&weekday) { "weekday enabled selected" } else { "weekday enabled" },
+                        onclick: {
+                            let mut schedule = habit.schedule.clone();
+                            move |_| {
+                                schedule.toggle(weekday);
+                                cmd.send(RitualCmd::SetHabitSchedule {
+                                    habit_id,
+                                    schedule: schedule.clone(),
+                                });
+                            }
+                        },
+                        {i18n::weekday_initial(i18n::locale(), weekday)}
+                    }
+                }
+            }
+            input {
+                class: "reminder",
+                r#type: "time",
+                value: "{reminder}",
+                onchange: move |e| {
+                    let reminder = NaiveTime::parse_from_str(&e.data.value(), "%H:%M").ok();
+                    cmd.send(RitualCmd::SetHabitReminder { habit_id, reminder });
+                }
+            }
+            div {
+                class: "accent",
+                input {
+                    r#type: "color",
+                    value: habit.color.clone().unwrap_or_else(|| "#8f3f71".to_string()),
+                    onchange: move |e| {
+                        cmd.send(RitualCmd::SetHabitColor { habit_id, color: Some(e.data.value()) });
+                    }
+                }
+                if habit.color.is_some() {
+                    Button {
+                        class: "enabled",
+                        onclick: move |_| cmd.send(RitualCmd::SetHabitColor { habit_id, color: None }),
+                        {t!("habit-color-reset")}
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum RitualCmd {
     NewDay,
     AddHabitToDay {
@@ -339,5 +1742,327 @@ pub enum RitualCmd {
         habit_id: Uuid,
         done: bool,
     },
+    HabitSetStatus {
+        day_id: Uuid,
+        habit_id: Uuid,
+        status: HabitStatus,
+    },
+    SetHabitReminder {
+        habit_id: Uuid,
+        reminder: Option<NaiveTime>,
+    },
+    SetHabitSchedule {
+        habit_id: Uuid,
+        schedule: types::Schedule,
+    },
+    SetHabitColor {
+        habit_id: Uuid,
+        color: Option<String>,
+    },
+    SetHabitNote {
+        day_id: Uuid,
+        habit_id: Uuid,
+        note: String,
+    },
+    SetDayJournal {
+        day_id: Uuid,
+        journal: String,
+    },
+    /// Rates the mood of a day from 1 to 5, or clears it
+    SetDayMood {
+        day_id: Uuid,
+        mood: Option<u8>,
+    },
+    RenameHabit {
+        habit_id: Uuid,
+        title: String,
+    },
+    /// Archives a habit so it is no longer added to new days, or brings it back
+    SetHabitArchived {
+        habit_id: Uuid,
+        archived: bool,
+    },
+    /// Pauses one habit, or all of them when `habit_id` is unset, from `start`
+    /// through `end`
+    AddPause {
+        start: NaiveDate,
+        end: NaiveDate,
+        habit_id: Option<Uuid>,
+    },
+    /// Ends a pause as of today
+    EndPause {
+        pause_id: Uuid,
+    },
     Save,
+    /// Saves over the changes another program made to the database file, settling
+    /// a conflict in favor of the state in memory. Only ever sent from the app
+    /// itself.
+    #[serde(skip)]
+    KeepLocal,
+    /// Picks up changes another program made to the database file
+    Reload,
+    /// Trades changes with other devices through the sync server
+    Sync,
+    /// Unlocks an encrypted database. Only ever sent from the app itself.
+    #[serde(skip)]
+    Unlock {
+        passphrase: Passphrase,
+    },
+    /// Saves and moves over to another profile, `None` being the default one
+    #[serde(skip)]
+    SwitchProfile {
+        profile: Option<String>,
+    },
+    /// Saves and moves over to the database of `config`, the settings as they go
+    /// into the config file. Only ever sent from the app itself.
+    #[serde(skip)]
+    SwitchDatabase {
+        config: Box<Config>,
+    },
+}
+
+/// Whether the database has to be unlocked before anything can be shown
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum LockStatus {
+    /// The database has not been read yet
+    #[default]
+    Loading,
+    Unlocked,
+    /// Waiting for the passphrase, `failed` if the last one was wrong
+    Locked {
+        failed: bool,
+    },
+    /// The database could not be read, so there is nothing to show
+    Failed {
+        error: String,
+    },
+}
+
+/// How the state in memory relates to the database file
+#[derive(Debug, Clone, Default)]
+pub struct SyncStatus {
+    /// There are changes that have not been saved yet
+    pub dirty: bool,
+    /// What another program wrote to the database while there were unsaved changes
+    pub conflict: Option<State>,
+}
+
+impl RitualCmd {
+    /// What the command changes, for history messages. `None` for commands that
+    /// do not change the state.
+    pub fn describe(&self, state: &State) -> Option<String> {
+        let habit_title = |habit_id: &Uuid| {
+            state
+                .habits
+                .get(habit_id)
+                .map(|habit| habit.title.clone())
+                .unwrap_or_else(|| habit_id.to_string())
+        };
+        match self {
+            RitualCmd::NewDay => Some("Add day".to_string()),
+            RitualCmd::AddHabitToDay { title, .. } => Some(format!("Add habit {:?}", title)),
+            RitualCmd::HabitSetDone { habit_id, done, .. } => Some(format!(
+                "{} {:?}",
+                if *done { "Check" } else { "Uncheck" },
+                habit_title(habit_id)
+            )),
+            RitualCmd::HabitSetStatus {
+                habit_id, status, ..
+            } => Some(format!("Mark {:?} as {}", habit_title(habit_id), status)),
+            RitualCmd::SetHabitReminder { habit_id, .. } => {
+                Some(format!("Set reminder of {:?}", habit_title(habit_id)))
+            }
+            RitualCmd::SetHabitSchedule { habit_id, .. } => {
+                Some(format!("Change schedule of {:?}", habit_title(habit_id)))
+            }
+            RitualCmd::SetHabitColor { habit_id, .. } => {
+                Some(format!("Change color of {:?}", habit_title(habit_id)))
+            }
+            RitualCmd::SetHabitNote { habit_id, .. } => {
+                Some(format!("Edit note of {:?}", habit_title(habit_id)))
+            }
+            RitualCmd::RenameHabit { habit_id, title } => {
+                Some(format!("Rename {:?} to {:?}", habit_title(habit_id), title))
+            }
+            RitualCmd::SetHabitArchived { habit_id, archived } => Some(format!(
+                "{} {:?}",
+                if *archived { "Archive" } else { "Unarchive" },
+                habit_title(habit_id)
+            )),
+            RitualCmd::SetDayJournal { .. } => Some("Edit journal".to_string()),
+            RitualCmd::SetDayMood { mood, .. } => Some(match mood {
+                Some(mood) => format!("Rate mood {}/5", mood),
+                None => "Clear mood".to_string(),
+            }),
+            RitualCmd::AddPause { end, habit_id, .. } => Some(match habit_id {
+                Some(habit_id) => format!("Pause {:?} until {}", habit_title(habit_id), end),
+                None => format!("Pause all habits until {}", end),
+            }),
+            RitualCmd::EndPause { pause_id } => Some(
+                match state.pauses.get(pause_id).and_then(|pause| pause.habit_id) {
+                    Some(habit_id) => format!("Resume {:?}", habit_title(&habit_id)),
+                    None => "Resume all habits".to_string(),
+                },
+            ),
+            RitualCmd::Save
+            | RitualCmd::KeepLocal
+            | RitualCmd::Reload
+            | RitualCmd::Sync
+            | RitualCmd::Unlock { .. }
+            | RitualCmd::SwitchProfile { .. }
+            | RitualCmd::SwitchDatabase { .. } => None,
+        }
+    }
+
+    /// Applies the command to the state. Reading and writing the database file and
+    /// syncing are up to the caller, so `Save`, `Reload` and `Sync` do nothing here.
+    pub fn apply(self, state: &mut State, calendar: &config::Calendar) -> Result<()> {
+        match self {
+            RitualCmd::NewDay => {
+                let date: DateTime = chrono::Utc::now();
+                info!("Adding new day for date: {}", date);
+                state
+                    .add_day(date, calendar)
+                    .wrap_err("Failed to add new day")
+            }
+            RitualCmd::AddHabitToDay { title, day_id } => {
+                info!("Adding habit to day: {}", day_id);
+                let title = NonEmpty::new_validated(title)
+                    .map_err(|e| eyre::eyre!("Invalid habit title {e}"))?;
+                state
+                    .add_habit_to_day(title, day_id)
+                    .wrap_err_with(|| format!("Failed to add habit to day {}", day_id))
+            }
+            RitualCmd::HabitSetDone {
+                day_id,
+                habit_id,
+                done,
+            } => {
+                info!(
+                    "Setting habit {} for day {} to done: {}",
+                    habit_id, day_id, done
+                );
+                let status = if done {
+                    HabitStatus::Done
+                } else {
+                    HabitStatus::Pending
+                };
+                state
+                    .set_habit_status(day_id, habit_id, status)
+                    .wrap_err_with(|| {
+                        format!(
+                            "Failed to set habit {} for day {} to done",
+                            habit_id, day_id
+                        )
+                    })
+            }
+            RitualCmd::HabitSetStatus {
+                day_id,
+                habit_id,
+                status,
+            } => {
+                info!(
+                    "Setting habit {} for day {} to {}",
+                    habit_id, day_id, status
+                );
+                state
+                    .set_habit_status(day_id, habit_id, status)
+                    .wrap_err_with(|| {
+                        format!(
+                            "Failed to set habit {} for day {} to {}",
+                            habit_id, day_id, status
+                        )
+                    })
+            }
+            RitualCmd::SetHabitReminder { habit_id, reminder } => {
+                info!("Setting reminder of habit {} to {:?}", habit_id, reminder);
+                state
+                    .set_habit_reminder(habit_id, reminder)
+                    .wrap_err_with(|| format!("Failed to set reminder of habit {}", habit_id))
+            }
+            RitualCmd::SetHabitSchedule { habit_id, schedule } => {
+                info!("Setting schedule of habit {} to {:?}", habit_id, schedule);
+                state
+                    .set_habit_schedule(habit_id, schedule)
+                    .wrap_err_with(|| format!("Failed to set schedule of habit {}", habit_id))
+            }
+            RitualCmd::SetHabitColor { habit_id, color } => {
+                info!("Setting color of habit {} to {:?}", habit_id, color);
+                let color = color
+                    .map(HexColor::new_validated)
+                    .transpose()
+                    .map_err(|e| eyre::eyre!("Invalid habit color {e}"))?;
+                state
+                    .set_habit_color(habit_id, color)
+                    .wrap_err_with(|| format!("Failed to set color of habit {}", habit_id))
+            }
+            RitualCmd::SetHabitNote {
+                day_id,
+                habit_id,
+                note,
+            } => {
+                info!("Setting note of habit {} for day {}", habit_id, day_id);
+                state
+                    .set_habit_note(day_id, habit_id, note)
+                    .wrap_err_with(|| {
+                        format!(
+                            "Failed to set note of habit {} for day {}",
+                            habit_id, day_id
+                        )
+                    })
+            }
+            RitualCmd::RenameHabit { habit_id, title } => {
+                info!("Renaming habit {} to {:?}", habit_id, title);
+                let title = NonEmpty::new_validated(title)
+                    .map_err(|e| eyre::eyre!("Invalid habit title {e}"))?;
+                state
+                    .rename_habit(habit_id, title)
+                    .wrap_err_with(|| format!("Failed to rename habit {}", habit_id))
+            }
+            RitualCmd::SetHabitArchived { habit_id, archived } => {
+                info!("Setting habit {} archived: {}", habit_id, archived);
+                state
+                    .set_habit_archived(habit_id, archived)
+                    .wrap_err_with(|| {
+                        let action = if archived { "archive" } else { "unarchive" };
+                        format!("Failed to {} habit {}", action, habit_id)
+                    })
+            }
+            RitualCmd::SetDayJournal { day_id, journal } => {
+                info!("Setting journal of day {}", day_id);
+                state
+                    .set_day_journal(day_id, journal)
+                    .wrap_err_with(|| format!("Failed to set journal of day {}", day_id))
+            }
+            RitualCmd::SetDayMood { day_id, mood } => {
+                info!("Setting mood of day {} to {:?}", day_id, mood);
+                state
+                    .set_day_mood(day_id, mood)
+                    .wrap_err_with(|| format!("Failed to set mood of day {}", day_id))
+            }
+            RitualCmd::AddPause {
+                start,
+                end,
+                habit_id,
+            } => {
+                info!("Pausing habit {:?} from {} to {}", habit_id, start, end);
+                state
+                    .add_pause(types::Pause::new(start, end, habit_id))
+                    .wrap_err("Failed to add pause")
+            }
+            RitualCmd::EndPause { pause_id } => {
+                info!("Ending pause {}", pause_id);
+                state
+                    .end_pause(pause_id, calendar)
+                    .wrap_err_with(|| format!("Failed to end pause {}", pause_id))
+            }
+            RitualCmd::Save
+            | RitualCmd::KeepLocal
+            | RitualCmd::Reload
+            | RitualCmd::Sync
+            | RitualCmd::Unlock { .. }
+            | RitualCmd::SwitchProfile { .. }
+            | RitualCmd::SwitchDatabase { .. } => Ok(()),
+        }
+    }
 }
@@ -0,0 +1,86 @@
+//! Rendering journal entries, which are written in markdown.
+
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+
+/// Schemes links and images may point to, anything else could run script
+const SAFE_SCHEMES: [&str; 3] = ["http://", "https://", "mailto:"];
+
+/// Renders markdown to HTML. Raw HTML in the input is shown as text rather than
+/// passed through, and links and images lose destinations outside of
+/// [`SAFE_SCHEMES`], so an entry cannot inject markup or script into the app.
+pub fn to_html(markdown: &str) -> String {
+    let parser = Parser::new_ext(
+        markdown,
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS | Options::ENABLE_TABLES,
+    )
+    .map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    });
+    let mut output = String::new();
+    html::push_html(&mut output, parser);
+    output
+}
+
+fn safe(url: CowStr) -> CowStr {
+    let lowercase = url.trim_start().to_lowercase();
+    if SAFE_SCHEMES
+        .iter()
+        .any(|scheme| lowercase.starts_with(scheme))
+    {
+        url
+    } else {
+        CowStr::Borrowed("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_raw_html() {
+        assert_eq!(
+            to_html("**calm** day"),
+            "<p><strong>calm</strong> day</p>\n"
+        );
+        assert_eq!(
+            to_html("<script>alert(1)</script>"),
+            "&lt;script&gt;alert(1)&lt;/script&gt;"
+        );
+        assert_eq!(
+            to_html("[x](javascript:alert(1)) ![y](JavaScript:alert(1))"),
+            "<p><a href=\"\">x</a> <img src=\"\" alt=\"y\" /></p>\n"
+        );
+        assert_eq!(
+            to_html("[x](data:text/html,hi) [mail](mailto:a@b.c)"),
+            "<p><a href=\"\">x</a> <a href=\"mailto:a@b.c\">mail</a></p>\n"
+        );
+        assert_eq!(
+            to_html("[site](https://example.com)"),
+            "<p><a href=\"https://example.com\">site</a></p>\n"
+        );
+    }
+}
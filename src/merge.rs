@@ -0,0 +1,366 @@
+//! Reconciling two copies of a database that were edited separately, such as the
+//! conflict copies file sync tools leave behind.
+
+use chrono::NaiveDate;
+use itertools::Itertools;
+use std::fmt::Display;
+
+use crate::config::Calendar;
+use crate::db::State;
+use crate::types::HabitStatus;
+use crate::utils::differs;
+
+/// Which status to keep when a habit's differs between the copies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DonePolicy {
+    /// Done if it is done in either copy, otherwise whichever copy is not pending
+    #[default]
+    Done,
+    /// Not done unless it is done in both copies
+    NotDone,
+    /// Keep the first copy
+    First,
+    /// Keep the second copy
+    Second,
+}
+
+impl DonePolicy {
+    fn resolve(&self, first: HabitStatus, second: HabitStatus) -> HabitStatus {
+        // The copy that says more about how the day went
+        let settled = if first == HabitStatus::Pending {
+            second
+        } else {
+            first
+        };
+        match self {
+            DonePolicy::Done if first == HabitStatus::Done || second == HabitStatus::Done => {
+                HabitStatus::Done
+            }
+            DonePolicy::Done => settled,
+            DonePolicy::NotDone if first == HabitStatus::Done => second,
+            DonePolicy::NotDone => first,
+            DonePolicy::First => first,
+            DonePolicy::Second => second,
+        }
+    }
+}
+
+/// A difference between the two copies and how it was settled
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conflict {
+    /// A habit's settings differ, the first copy's are kept
+    Habit { title: String },
+    /// Each copy added its own day for the same date, they are merged into one
+    Day { date: NaiveDate },
+    Status {
+        date: NaiveDate,
+        habit: String,
+        first: HabitStatus,
+        second: HabitStatus,
+        kept: HabitStatus,
+    },
+    /// Both copies wrote a different journal entry or mood for the day, the
+    /// first copy's are kept
+    Journal { date: NaiveDate },
+    /// The note of a check-in differs, the first copy's is kept unless only the
+    /// second has one
+    Note {
+        date: NaiveDate,
+        habit: String,
+        kept_first: bool,
+    },
+    /// A pause was changed in one copy, such as ended early, the first copy's is kept
+    Pause { start: NaiveDate },
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Conflict::Habit { title } => {
+                write!(f, "{title:?} has different settings, kept the first")
+            }
+            Conflict::Day { date } => write!(f, "{date}: both copies added the day, merged"),
+            Conflict::Status {
+                date,
+                habit,
+                first,
+                second,
+                kept,
+            } => write!(
+                f,
+                "{date}: {habit:?} is {first} in the first and {second} in the second, kept {kept}"
+            ),
+            Conflict::Journal { date } => {
+                write!(f, "{date}: the journal differs, kept the first")
+            }
+            Conflict::Note {
+                date,
+                habit,
+                kept_first: true,
+            } => write!(f, "{date}: the note of {habit:?} differs, kept the first"),
+            Conflict::Note {
+                date,
+                habit,
+                kept_first: false,
+            } => write!(
+                f,
+                "{date}: only the second has a note for {habit:?}, kept it"
+            ),
+            Conflict::Pause { start } => {
+                write!(f, "The pause from {start} differs, kept the first")
+            }
+        }
+    }
+}
+
+/// Unions the days, habits and pauses of both states by id, and days by date when each
+/// copy added its own
+pub fn merge(
+    first: &State,
+    second: &State,
+    policy: DonePolicy,
+    calendar: &Calendar,
+) -> (State, Vec<Conflict>) {
+    let mut state = first.clone();
+    let mut conflicts = Vec::new();
+
+    for habit in second.habits.values() {
+        match state.habits.get(&habit.id) {
+            None => {
+                state.habits.insert(habit.id, habit.clone());
+            }
+            Some(existing) if differs(existing, habit) => conflicts.push(Conflict::Habit {
+                title: existing.title.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for pause in second.pauses.values() {
+        match state.pauses.get(&pause.id) {
+            None => {
+                state.pauses.insert(pause.id, pause.clone());
+            }
+            Some(existing) if existing != pause => conflicts.push(Conflict::Pause {
+                start: existing.start,
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for day in second.days.values().sorted_by_key(|day| day.date) {
+        let date = calendar.date_of(day.date);
+        let target = if state.days.contains_key(&day.id) {
+            Some(day.id)
+        } else {
+            state.day_on(date, calendar).map(|existing| existing.id)
+        };
+        let Some(target) = target else {
+            state.days.insert(day.id, day.clone());
+            continue;
+        };
+        if target != day.id {
+            conflicts.push(Conflict::Day { date });
+        }
+        let target = state.days.get_mut(&target).expect("day was just found");
+        // The entry and the mood are separate fields, one copy may only have one
+        let mut conflict = false;
+        match (&target.journal, &day.journal) {
+            (None, Some(journal)) => target.journal = Some(journal.clone()),
+            (Some(first), Some(second)) if first != second => conflict = true,
+            _ => {}
+        }
+        match (target.mood, day.mood) {
+            (None, Some(mood)) => target.mood = Some(mood),
+            (Some(first), Some(second)) if first != second => conflict = true,
+            _ => {}
+        }
+        if conflict {
+            conflicts.push(Conflict::Journal { date });
+        }
+        for habit in day.habits.values() {
+            let Some(existing) = target.habits.get_mut(&habit.id) else {
+                target.habits.insert(habit.id, habit.clone());
+                continue;
+            };
+            let name = state
+                .habits
+                .get(&habit.id)
+                .map_or(&existing.name, |h| &h.title)
+                .clone();
+            if habit.note.is_some() && existing.note != habit.note {
+                let kept_first = existing.note.is_some();
+                if !kept_first {
+                    existing.note = habit.note.clone();
+                }
+                conflicts.push(Conflict::Note {
+                    date,
+                    habit: name.clone(),
+                    kept_first,
+                });
+            }
+            if existing.status != habit.status {
+                let kept = policy.resolve(existing.status, habit.status);
+                conflicts.push(Conflict::Status {
+                    date,
+                    habit: name,
+                    first: existing.status,
+                    second: habit.status,
+                    kept,
+                });
+                existing.status = kept;
+            }
+        }
+    }
+
+    (state, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Pause;
+    use crate::utils::{NonEmpty, Validate};
+
+    #[test]
+    fn merges_days_of_the_same_date() {
+        let calendar = Calendar {
+            timezone: None,
+            rollover_hour: 0,
+        };
+        let now = chrono::Utc::now();
+        let mut first = State::default();
+        first.add_day(now, &calendar).unwrap();
+        let day_id = *first.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Run".to_string()).unwrap();
+        first.add_habit_to_day(title, day_id).unwrap();
+        let habit_id = *first.habits.keys().next().unwrap();
+
+        // The second copy added today again and checked the habit there
+        let mut second = first.clone();
+        second.days.clear();
+        second.add_day(now, &calendar).unwrap();
+        let other_day_id = *second.days.keys().next().unwrap();
+        second
+            .set_habit_status(other_day_id, habit_id, HabitStatus::Done)
+            .unwrap();
+
+        let (merged, conflicts) = merge(&first, &second, DonePolicy::Done, &calendar);
+        assert_eq!(merged.days.len(), 1);
+        assert_eq!(
+            merged.days[&day_id].habits[&habit_id].status,
+            HabitStatus::Done
+        );
+        assert_eq!(conflicts.len(), 2);
+
+        let (merged, _) = merge(&first, &second, DonePolicy::First, &calendar);
+        assert_eq!(
+            merged.days[&day_id].habits[&habit_id].status,
+            HabitStatus::Pending
+        );
+    }
+
+    #[test]
+    fn merges_journal_and_mood_separately() {
+        let calendar = Calendar {
+            timezone: None,
+            rollover_hour: 0,
+        };
+        let mut first = State::default();
+        first.add_day(chrono::Utc::now(), &calendar).unwrap();
+        let day_id = *first.days.keys().next().unwrap();
+        let mut second = first.clone();
+        first
+            .set_day_journal(day_id, "Slept well".to_string())
+            .unwrap();
+        second.set_day_mood(day_id, Some(4)).unwrap();
+
+        let (merged, conflicts) = merge(&first, &second, DonePolicy::Done, &calendar);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged.days[&day_id].journal.as_deref(), Some("Slept well"));
+        assert_eq!(merged.days[&day_id].mood, Some(4));
+
+        // Both set a mood, and they disagree
+        first.set_day_mood(day_id, Some(2)).unwrap();
+        let (merged, conflicts) = merge(&first, &second, DonePolicy::Done, &calendar);
+        assert!(matches!(conflicts[..], [Conflict::Journal { .. }]));
+        assert_eq!(merged.days[&day_id].mood, Some(2));
+    }
+
+    #[test]
+    fn reports_differing_notes() {
+        let calendar = Calendar {
+            timezone: None,
+            rollover_hour: 0,
+        };
+        let mut first = State::default();
+        first.add_day(chrono::Utc::now(), &calendar).unwrap();
+        let day_id = *first.days.keys().next().unwrap();
+        for title in ["Run", "Read"] {
+            let title = NonEmpty::new_validated(title.to_string()).unwrap();
+            first.add_habit_to_day(title, day_id).unwrap();
+        }
+        let (run, read) = {
+            let mut ids = first.habits.keys().copied();
+            (ids.next().unwrap(), ids.next().unwrap())
+        };
+        let mut second = first.clone();
+        first.set_habit_note(day_id, run, "5K".to_string()).unwrap();
+        second
+            .set_habit_note(day_id, run, "10K".to_string())
+            .unwrap();
+        second
+            .set_habit_note(day_id, read, "Dune".to_string())
+            .unwrap();
+
+        let (merged, conflicts) = merge(&first, &second, DonePolicy::Done, &calendar);
+        let habits = &merged.days[&day_id].habits;
+        assert_eq!(habits[&run].note.as_deref(), Some("5K"));
+        assert_eq!(habits[&read].note.as_deref(), Some("Dune"));
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                format!(
+                    "{}: the note of \"Run\" differs, kept the first",
+                    calendar.today()
+                ),
+                format!(
+                    "{}: only the second has a note for \"Read\", kept it",
+                    calendar.today()
+                ),
+            ]
+        );
+        // The same note is no conflict
+        let (_, conflicts) = merge(&merged, &merged, DonePolicy::Done, &calendar);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn merges_pauses() {
+        let calendar = Calendar {
+            timezone: None,
+            rollover_hour: 0,
+        };
+        let today = calendar.today();
+        let pause = Pause::new(today, today + chrono::TimeDelta::days(3), None);
+        let (mut first, mut second) = (State::default(), State::default());
+        second.add_pause(pause.clone()).unwrap();
+
+        let (merged, conflicts) = merge(&first, &second, DonePolicy::Done, &calendar);
+        assert_eq!(merged.pauses[&pause.id], pause);
+        assert!(conflicts.is_empty());
+
+        // One copy ended the pause early, the first copy's end is kept
+        first.add_pause(pause.clone()).unwrap();
+        second.end_pause(pause.id, &calendar).unwrap();
+        let (merged, conflicts) = merge(&first, &second, DonePolicy::Done, &calendar);
+        assert_eq!(merged.pauses[&pause.id], pause);
+        assert!(matches!(
+            conflicts[..],
+            [Conflict::Pause { start }] if start == today
+        ));
+    }
+}
@@ -0,0 +1,276 @@
+use chrono::{NaiveDate, NaiveTime};
+use std::collections::HashSet;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config::Calendar;
+use crate::db::State;
+use crate::i18n::t;
+use crate::prelude::*;
+use crate::types::{DateTime, HabitStatus};
+
+/// How often the scheduler checks for due reminders
+pub const TICK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    pub habit_id: Uuid,
+    pub title: String,
+    pub time: NaiveTime,
+}
+
+pub trait Notifier {
+    fn notify(&self, reminder: &Reminder) -> Result<()>;
+}
+
+/// Shows reminders as desktop notifications
+#[cfg(not(feature = "web"))]
+pub struct DesktopNotifier;
+
+#[cfg(not(feature = "web"))]
+impl Notifier for DesktopNotifier {
+    fn notify(&self, reminder: &Reminder) -> Result<()> {
+        notify_rust::Notification::new()
+            .appname("ritual")
+            .summary(&reminder.title)
+            .body(&t!(
+                "reminder-body",
+                time = reminder.time.format("%H:%M").to_string()
+            ))
+            .show()?;
+        Ok(())
+    }
+}
+
+/// Shows reminders through the browser's Notification API
+#[cfg(feature = "web")]
+pub struct BrowserNotifier;
+
+#[cfg(feature = "web")]
+impl BrowserNotifier {
+    pub fn new() -> Self {
+        if web_sys::Notification::permission() == web_sys::NotificationPermission::Default {
+            let _ = web_sys::Notification::request_permission();
+        }
+        Self
+    }
+}
+
+#[cfg(feature = "web")]
+impl Notifier for BrowserNotifier {
+    fn notify(&self, reminder: &Reminder) -> Result<()> {
+        let options = web_sys::NotificationOptions::new();
+        options.set_body(&t!(
+            "reminder-body",
+            time = reminder.time.format("%H:%M").to_string()
+        ));
+        web_sys::Notification::new_with_options(&reminder.title, &options)
+            .map_err(|err| color_eyre::eyre::eyre!("Failed to show notification: {:?}", err))?;
+        Ok(())
+    }
+}
+
+/// The notifier the GUI uses on the current platform
+#[cfg(not(feature = "web"))]
+pub fn platform_notifier() -> impl Notifier {
+    DesktopNotifier
+}
+
+#[cfg(feature = "web")]
+pub fn platform_notifier() -> impl Notifier {
+    BrowserNotifier::new()
+}
+
+/// Writes one line per reminder to any writer, e.g. stdout or a log file
+#[cfg(not(feature = "web"))]
+pub struct WriterNotifier<W: std::io::Write> {
+    writer: std::sync::Mutex<W>,
+}
+
+#[cfg(not(feature = "web"))]
+impl<W: std::io::Write> WriterNotifier<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: std::sync::Mutex::new(writer),
+        }
+    }
+}
+
+#[cfg(not(feature = "web"))]
+impl WriterNotifier<std::io::Stdout> {
+    pub fn stdout() -> Self {
+        Self::new(std::io::stdout())
+    }
+}
+
+#[cfg(not(feature = "web"))]
+impl WriterNotifier<std::fs::File> {
+    pub fn file(path: &std::path::Path) -> Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self::new(file))
+    }
+}
+
+#[cfg(not(feature = "web"))]
+impl<W: std::io::Write> Notifier for WriterNotifier<W> {
+    fn notify(&self, reminder: &Reminder) -> Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("Notifier writer lock poisoned"))?;
+        writeln!(
+            writer,
+            "{} {} {}",
+            reminder.time.format("%H:%M"),
+            reminder.habit_id,
+            reminder.title
+        )?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Reminders of today's habits that are not done, not paused and whose reminder time
+/// has passed
+pub fn due_reminders(state: &State, now: DateTime, calendar: &Calendar) -> Vec<Reminder> {
+    let today = calendar.date_of(now);
+    let local_time = calendar.time_of(now);
+    state
+        .days
+        .values()
+        .filter(|day| calendar.date_of(day.date) == today)
+        .flat_map(|day| day.habits.values())
+        .filter(|habit_ref| habit_ref.status == HabitStatus::Pending)
+        .filter(|habit_ref| !state.is_paused(habit_ref.id, today))
+        .filter_map(|habit_ref| {
+            let habit = state.habits.get(&habit_ref.id)?;
+            let time = habit.reminder?;
+            (time <= local_time).then(|| Reminder {
+                habit_id: habit.id,
+                title: habit.title.clone(),
+                time,
+            })
+        })
+        .collect()
+}
+
+/// Fires each due reminder once per day
+pub struct Scheduler<N: Notifier> {
+    notifier: N,
+    fired: HashSet<(Uuid, NaiveDate)>,
+}
+
+impl<N: Notifier> Scheduler<N> {
+    pub fn new(notifier: N) -> Self {
+        Self {
+            notifier,
+            fired: HashSet::new(),
+        }
+    }
+
+    pub fn tick(&mut self, state: &State, now: DateTime, calendar: &Calendar) {
+        let today = calendar.date_of(now);
+        self.fired.retain(|(_, date)| *date == today);
+        for reminder in due_reminders(state, now, calendar) {
+            if !self.fired.insert((reminder.habit_id, today)) {
+                continue;
+            }
+            info!("Reminding about habit: {}", reminder.title);
+            self.notifier.notify(&reminder).unwrap_or_else(|err| {
+                error!("Failed to send reminder for {}: {}", reminder.title, err);
+            });
+        }
+    }
+}
+
+/// Headless mode: periodically reloads the database and fires reminders
+#[cfg(not(feature = "web"))]
+pub async fn run_daemon<N: Notifier>(
+    db: crate::db::Db,
+    notifier: N,
+    calendar: Calendar,
+) -> Result<()> {
+    let mut scheduler = Scheduler::new(notifier);
+    let mut interval = tokio::time::interval(TICK_INTERVAL);
+    loop {
+        interval.tick().await;
+        match db.load() {
+            Ok(state) => scheduler.tick(&state, chrono::Utc::now(), &calendar),
+            Err(err) => error!("Failed to load db: {}", err),
+        }
+    }
+}
+
+// The writer notifier is native only
+#[cfg(all(test, not(feature = "web")))]
+mod tests {
+    use super::*;
+    use crate::utils::{NonEmpty, Validate};
+
+    const CALENDAR: Calendar = Calendar {
+        timezone: Some(chrono_tz::UTC),
+        rollover_hour: 0,
+    };
+
+    fn at(time: &str) -> DateTime {
+        format!("2025-03-05T{time}Z").parse().unwrap()
+    }
+
+    /// A day with a habit to be reminded about at 08:00
+    fn state() -> (State, Uuid, Uuid) {
+        let mut state = State::default();
+        state.add_day(at("07:00:00"), &CALENDAR).unwrap();
+        let day_id = *state.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Stretch".to_string()).unwrap();
+        state.add_habit_to_day(title, day_id).unwrap();
+        let habit_id = *state.habits.keys().next().unwrap();
+        state.habits[&habit_id].reminder = NaiveTime::from_hms_opt(8, 0, 0);
+        (state, day_id, habit_id)
+    }
+
+    fn written(scheduler: &Scheduler<WriterNotifier<Vec<u8>>>) -> Vec<String> {
+        let writer = scheduler.notifier.writer.lock().unwrap();
+        String::from_utf8(writer.clone())
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn due_after_reminder_time() {
+        let (state, _, habit_id) = state();
+        assert_eq!(due_reminders(&state, at("07:59:00"), &CALENDAR), []);
+        let due = due_reminders(&state, at("08:00:00"), &CALENDAR);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].habit_id, habit_id);
+        assert_eq!(due[0].title, "Stretch");
+    }
+
+    #[test]
+    fn fires_once_per_day() {
+        let (state, _, habit_id) = state();
+        let mut scheduler = Scheduler::new(WriterNotifier::new(Vec::new()));
+        scheduler.tick(&state, at("07:30:00"), &CALENDAR);
+        assert!(written(&scheduler).is_empty());
+        scheduler.tick(&state, at("08:00:30"), &CALENDAR);
+        scheduler.tick(&state, at("08:01:00"), &CALENDAR);
+        scheduler.tick(&state, at("21:00:00"), &CALENDAR);
+        assert_eq!(written(&scheduler), [format!("08:00 {habit_id} Stretch")]);
+    }
+
+    #[test]
+    fn skips_done_habits() {
+        let (mut state, day_id, habit_id) = state();
+        state
+            .set_habit_status(day_id, habit_id, HabitStatus::Done)
+            .unwrap();
+        assert_eq!(due_reminders(&state, at("09:00:00"), &CALENDAR), []);
+        let mut scheduler = Scheduler::new(WriterNotifier::new(Vec::new()));
+        scheduler.tick(&state, at("09:00:00"), &CALENDAR);
+        assert!(written(&scheduler).is_empty());
+    }
+}
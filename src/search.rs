@@ -0,0 +1,346 @@
+//! Searching habit titles, check-in notes and day journals across all days.
+//!
+//! A query is a list of words that must all be found, ignoring case, with words in
+//! double quotes matched as one phrase. Filters narrow it down: `from:2025-01-01`
+//! and `to:2025-01-31` limit the dates, both ends included, and `is:done`,
+//! `is:not-done`, `is:pending`, `is:skipped` or `is:failed` only keep check-ins
+//! with that status.
+
+use chrono::NaiveDate;
+use color_eyre::eyre;
+use std::ops::Range;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::config::Calendar;
+use crate::db::State;
+use crate::prelude::*;
+use crate::types::{HabitRef, HabitStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFilter {
+    Is(HabitStatus),
+    NotDone,
+}
+
+impl StatusFilter {
+    fn matches(self, status: HabitStatus) -> bool {
+        match self {
+            StatusFilter::Is(wanted) => status == wanted,
+            StatusFilter::NotDone => status != HabitStatus::Done,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    /// Lowercase words and phrases
+    pub terms: Vec<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub status: Option<StatusFilter>,
+}
+
+impl Query {
+    /// Whether there is nothing to search for
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.status.is_none()
+    }
+}
+
+/// Splits on whitespace, keeping text in double quotes together
+fn tokens(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                tokens.extend((!token.is_empty()).then(|| std::mem::take(&mut token)));
+            }
+            c => token.push(c),
+        }
+    }
+    tokens.extend((!token.is_empty()).then_some(token));
+    tokens
+}
+
+impl FromStr for Query {
+    type Err = eyre::Report;
+
+    fn from_str(query: &str) -> Result<Self> {
+        let date = |token: &str, date: &str| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| eyre::eyre!("Invalid date in {:?}, expected YYYY-MM-DD", token))
+        };
+        let mut parsed = Query::default();
+        for token in tokens(query) {
+            if let Some(from) = token.strip_prefix("from:") {
+                parsed.from = Some(date(&token, from)?);
+            } else if let Some(to) = token.strip_prefix("to:") {
+                parsed.to = Some(date(&token, to)?);
+            } else if let Some(status) = token.strip_prefix("is:") {
+                parsed.status = Some(match status {
+                    "done" => StatusFilter::Is(HabitStatus::Done),
+                    "pending" => StatusFilter::Is(HabitStatus::Pending),
+                    "skipped" => StatusFilter::Is(HabitStatus::Skipped),
+                    "failed" => StatusFilter::Is(HabitStatus::Failed),
+                    "not-done" => StatusFilter::NotDone,
+                    _ => return Err(eyre::eyre!("Unknown status in {:?}", token)),
+                });
+            } else {
+                parsed.terms.push(token.to_lowercase());
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// A piece of matched text, highlighted if it is one of the query terms
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub date: NaiveDate,
+    pub day_id: Uuid,
+    /// The check-in that matched, or `None` for the day's journal
+    pub habit: Option<HabitRef>,
+    pub snippet: Vec<Segment>,
+}
+
+/// Length of `term` at the start of `text` if it is there, ignoring case
+fn match_len(text: &str, term: &str) -> Option<usize> {
+    let mut wanted = term.chars().peekable();
+    for (offset, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            if wanted.next() != Some(lower) {
+                return None;
+            }
+        }
+        if wanted.peek().is_none() {
+            return Some(offset + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// Where `term`, in lowercase, occurs in `text`
+fn find(text: &str, term: &str) -> Vec<Range<usize>> {
+    let mut found = Vec::new();
+    let mut next = 0;
+    for (start, _) in text.char_indices() {
+        if start < next {
+            continue;
+        }
+        if let Some(len) = match_len(&text[start..], term) {
+            found.push(start..start + len);
+            next = start + len;
+        }
+    }
+    found
+}
+
+/// Cuts `text` into plain and highlighted segments
+fn highlight(text: &str, mut ranges: Vec<Range<usize>>) -> Vec<Segment> {
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    let mut segments = Vec::new();
+    let mut plain_start = 0;
+    for range in merged {
+        segments.push((plain_start..range.start, false));
+        plain_start = range.end;
+        segments.push((range, true));
+    }
+    segments.push((plain_start..text.len(), false));
+    segments
+        .into_iter()
+        .filter(|(range, _)| !range.is_empty())
+        .map(|(range, highlighted)| Segment {
+            text: text[range].to_string(),
+            highlighted,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    date: NaiveDate,
+    day_id: Uuid,
+    habit: Option<HabitRef>,
+    /// Kept apart because habits compare by id only
+    status: Option<HabitStatus>,
+    text: String,
+}
+
+/// The searchable text of every day, newest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index {
+    entries: Vec<Entry>,
+}
+
+impl Index {
+    pub fn new(state: &State, calendar: &Calendar) -> Self {
+        let mut entries = Vec::new();
+        for day in state.days.values() {
+            let date = calendar.date_of(day.date);
+            for habit in day.habits.values() {
+                let name = state.habit_name(habit);
+                let text = match &habit.note {
+                    Some(note) => format!("{}: {}", name, note),
+                    None => name.to_string(),
+                };
+                entries.push(Entry {
+                    date,
+                    day_id: day.id,
+                    habit: Some(habit.clone()),
+                    status: Some(habit.status),
+                    text,
+                });
+            }
+            if let Some(journal) = &day.journal {
+                entries.push(Entry {
+                    date,
+                    day_id: day.id,
+                    habit: None,
+                    status: None,
+                    text: journal.clone(),
+                });
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.date));
+        trace!("Indexed {} entries for search", entries.len());
+        Self { entries }
+    }
+
+    pub fn search(&self, query: &Query) -> Vec<Hit> {
+        self.entries
+            .iter()
+            .filter(|entry| query.from.is_none_or(|from| entry.date >= from))
+            .filter(|entry| query.to.is_none_or(|to| entry.date <= to))
+            .filter(|entry| match (entry.status, query.status) {
+                (_, None) => true,
+                (Some(status), Some(filter)) => filter.matches(status),
+                (None, Some(_)) => false,
+            })
+            .filter_map(|entry| {
+                let mut ranges = Vec::new();
+                for term in &query.terms {
+                    let found = find(&entry.text, term);
+                    if found.is_empty() {
+                        return None;
+                    }
+                    ranges.extend(found);
+                }
+                let snippet = match &entry.habit {
+                    Some(_) => highlight(&entry.text, ranges),
+                    // Journals can be long, so only show the line of the first match
+                    None => {
+                        let first = ranges.iter().map(|range| range.start).min()?;
+                        let line_start = entry.text[..first].rfind('\n').map_or(0, |i| i + 1);
+                        let line_end = entry.text[first..]
+                            .find('\n')
+                            .map_or(entry.text.len(), |i| first + i);
+                        let line = &entry.text[line_start..line_end];
+                        let ranges = ranges
+                            .into_iter()
+                            .filter(|range| range.start >= line_start && range.end <= line_end)
+                            .map(|range| range.start - line_start..range.end - line_start)
+                            .collect();
+                        highlight(line, ranges)
+                    }
+                };
+                Some(Hit {
+                    date: entry.date,
+                    day_id: entry.day_id,
+                    habit: entry.habit.clone(),
+                    snippet,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{NonEmpty, Validate};
+
+    fn highlighted(snippet: &[Segment]) -> Vec<&str> {
+        snippet
+            .iter()
+            .filter(|segment| segment.highlighted)
+            .map(|segment| segment.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parses_queries() {
+        let query: Query = "Run \"by the river\" from:2025-03-01 is:not-done"
+            .parse()
+            .unwrap();
+        assert_eq!(query.terms, ["run", "by the river"]);
+        assert_eq!(query.from, NaiveDate::from_ymd_opt(2025, 3, 1));
+        assert_eq!(query.to, None);
+        assert_eq!(query.status, Some(StatusFilter::NotDone));
+        assert!("is:maybe".parse::<Query>().is_err());
+        assert!("to:yesterday".parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn highlights_ignoring_case() {
+        let text = "Ran 5K, then RAN again";
+        let snippet = highlight(text, find(text, "ran"));
+        assert_eq!(highlighted(&snippet), ["Ran", "RAN"]);
+        assert_eq!(
+            snippet.iter().map(|s| s.text.as_str()).collect::<String>(),
+            text
+        );
+        let text = "Ópera y ÉXITO";
+        assert_eq!(
+            highlighted(&highlight(text, find(text, "éxito"))),
+            ["ÉXITO"]
+        );
+    }
+
+    #[test]
+    fn finds_notes_and_journals() {
+        let calendar = Calendar {
+            timezone: None,
+            rollover_hour: 0,
+        };
+        let mut state = State::default();
+        state.add_day(chrono::Utc::now(), &calendar).unwrap();
+        let day_id = *state.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Run".to_string()).unwrap();
+        state.add_habit_to_day(title, day_id).unwrap();
+        let habit_id = *state.habits.keys().next().unwrap();
+        state
+            .set_habit_note(day_id, habit_id, "Along the river".to_string())
+            .unwrap();
+        state
+            .set_day_journal(day_id, "Slept well\nSaw a heron by the river".to_string())
+            .unwrap();
+        let index = Index::new(&state, &calendar);
+
+        let hits = index.search(&"river".parse().unwrap());
+        assert_eq!(hits.len(), 2);
+        let journal = hits.iter().find(|hit| hit.habit.is_none()).unwrap();
+        assert_eq!(journal.snippet[0].text, "Saw a heron by the ");
+
+        assert_eq!(index.search(&"run river is:done".parse().unwrap()), []);
+        let pending = index.search(&"run river is:not-done".parse().unwrap());
+        assert_eq!(pending.len(), 1);
+        assert_eq!(highlighted(&pending[0].snippet), ["Run", "river"]);
+    }
+}
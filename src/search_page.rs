@@ -0,0 +1,74 @@
+use dioxus::prelude::*;
+
+use crate::config::Config;
+use crate::db::State;
+use crate::i18n::{self, t};
+use crate::search::{Index, Query};
+
+#[component]
+pub fn Search() -> Element {
+    let state = use_context::<Signal<State>>();
+    let config = use_context::<Signal<Config>>();
+    let mut text = use_signal(String::new);
+    // Only reindexed when the state changes, not on every keystroke
+    let index = use_memo(move || Index::new(&state.read(), &config.read().calendar()));
+    let today = config.read().calendar().today();
+    let query = text.read().parse::<Query>();
+
+    rsx! {
+        div {
+            class: "main search",
+            h1 { {t!("search-title")} }
+            input {
+                r#type: "search",
+                placeholder: t!("search-placeholder"),
+                value: "{text}",
+                oninput: move |e| text.set(e.data.value()),
+            }
+            match query {
+                Err(err) => rsx! {
+                    p { class: "error", "{err}" }
+                },
+                Ok(query) if query.is_empty() => rsx! {
+                    p { class: "hint", {t!("search-hint")} }
+                },
+                Ok(query) => {
+                    let hits = index.read().search(&query);
+                    rsx! {
+                        if hits.is_empty() {
+                            p { class: "hint", {t!("search-no-results")} }
+                        }
+                        ul {
+                            class: "hits",
+                            for hit in hits {
+                                li {
+                                    span {
+                                        class: "date",
+                                        {i18n::fmt_nice_date(hit.date, today, i18n::locale())}
+                                    }
+                                    span {
+                                        class: "kind",
+                                        match &hit.habit {
+                                            Some(habit) => t!(&format!("status-{}", habit.status)),
+                                            None => t!("search-journal"),
+                                        }
+                                    }
+                                    span {
+                                        class: "snippet",
+                                        for segment in hit.snippet {
+                                            if segment.highlighted {
+                                                mark { "{segment.text}" }
+                                            } else {
+                                                "{segment.text}"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use chrono::Weekday;
+use dioxus::prelude::*;
+use indexmap::IndexMap;
+
+use crate::components::button::Button;
+use crate::config::{
+    ApiConfig, Config, ConfigError, ConfigSource, DailyNotesConfig, Profile, Shortcuts, SyncConfig,
+    Theme,
+};
+use crate::i18n::{self, t, Locale};
+use crate::prelude::*;
+use crate::RitualCmd;
+
+/// The settings as typed into the form, before they are parsed into a [`Config`]
+#[derive(Debug, Clone, PartialEq)]
+struct ConfigForm {
+    db_path: String,
+    /// Profiles are managed elsewhere, they are only kept as they are
+    profile: Option<String>,
+    profiles: IndexMap<String, Profile>,
+    timezone: String,
+    day_rollover_hour: String,
+    week_start: String,
+    theme: String,
+    shortcuts: Shortcuts,
+    autosave_interval: String,
+    locale: String,
+    api_enabled: bool,
+    api_port: String,
+    api_token: String,
+    sync_server: String,
+    sync_interval: String,
+    daily_notes_folder: String,
+    daily_notes_template: String,
+    history: bool,
+}
+
+impl From<&Config> for ConfigForm {
+    fn from(config: &Config) -> Self {
+        Self {
+            db_path: config.db_path.display().to_string(),
+            profile: config.profile.clone(),
+            profiles: config.profiles.clone(),
+            timezone: config
+                .timezone
+                .map(|tz| tz.name().to_string())
+                .unwrap_or_default(),
+            day_rollover_hour: config.day_rollover_hour.to_string(),
+            week_start: config.week_start.to_string(),
+            theme: config.theme.to_string(),
+            shortcuts: config.shortcuts.clone(),
+            autosave_interval: config.autosave_interval.to_string(),
+            locale: config.locale.map(|l| l.to_string()).unwrap_or_default(),
+            api_enabled: config.api.enabled,
+            api_port: config.api.port.to_string(),
+            api_token: config.api.token.clone(),
+            sync_server: config.sync.server.clone().unwrap_or_default(),
+            sync_interval: config.sync.interval.to_string(),
+            daily_notes_folder: config
+                .daily_notes
+                .folder
+                .as_ref()
+                .map(|folder| folder.display().to_string())
+                .unwrap_or_default(),
+            daily_notes_template: config.daily_notes.template.clone(),
+            history: config.history,
+        }
+    }
+}
+
+impl ConfigForm {
+    fn parse(&self) -> std::result::Result<Config, Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        let mut error = |field, message: String| errors.push(ConfigError { field, message });
+
+        let timezone = match self.timezone.trim() {
+            "" => None,
+            tz => tz
+                .parse()
+                .map_err(|_| error("timezone", t!("error-timezone", timezone = tz)))
+                .ok(),
+        };
+        let day_rollover_hour = self
+            .day_rollover_hour
+            .trim()
+            .parse()
+            .map_err(|_| error("day_rollover_hour", t!("error-number")))
+            .unwrap_or_default();
+        let week_start = self
+            .week_start
+            .parse::<Weekday>()
+            .map_err(|_| error("week_start", t!("error-weekday")))
+            .unwrap_or(Weekday::Mon);
+        let theme = self
+            .theme
+            .parse::<Theme>()
+            .map_err(|_| error("theme", t!("error-theme")))
+            .unwrap_or_default();
+        let autosave_interval = self
+            .autosave_interval
+            .trim()
+            .parse()
+            .map_err(|_| error("autosave_interval", t!("error-number")))
+            .unwrap_or_default();
+        let locale = match self.locale.as_str() {
+            "" => None,
+            locale => locale
+                .parse::<Locale>()
+                .map_err(|_| error("locale", t!("error-locale")))
+                .ok(),
+        };
+        let api_port = self
+            .api_port
+            .trim()
+            .parse()
+            .map_err(|_| error("api.port", t!("error-port")))
+            .unwrap_or_default();
+        let sync_interval = self
+            .sync_interval
+            .trim()
+            .parse()
+            .map_err(|_| error("sync.interval", t!("error-number")))
+            .unwrap_or_default();
+
+        let config = Config {
+            db_path: self.db_path.trim().into(),
+            profile: self.profile.clone(),
+            profiles: self.profiles.clone(),
+            timezone,
+            day_rollover_hour,
+            week_start,
+            theme,
+            shortcuts: self.shortcuts.clone(),
+            autosave_interval,
+            locale,
+            api: ApiConfig {
+                enabled: self.api_enabled,
+                port: api_port,
+                token: self.api_token.trim().to_string(),
+            },
+            sync: SyncConfig {
+                server: Some(self.sync_server.trim().to_string()).filter(|s| !s.is_empty()),
+                interval: sync_interval,
+            },
+            daily_notes: DailyNotesConfig {
+                folder: Some(self.daily_notes_folder.trim())
+                    .filter(|folder| !folder.is_empty())
+                    .map(Into::into),
+                template: self.daily_notes_template.clone(),
+            },
+            history: self.history,
+        };
+        let invalid = config
+            .validate()
+            .into_iter()
+            .filter(|e| !errors.iter().any(|prev| prev.field == e.field))
+            .collect::<Vec<_>>();
+        errors.extend(invalid);
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[component]
+pub fn Settings() -> Element {
+    let source = use_context::<Signal<ConfigSource>>();
+    let mut config = use_context::<Signal<Config>>();
+    let mut form = use_signal(|| {
+        // Edit what is in the file, not the command line overrides
+        let file_config = Config::load(&source.read().path).unwrap_or_else(|err| {
+            error!("Failed to load config: {}", err);
+            config.read().clone()
+        });
+        ConfigForm::from(&file_config)
+    });
+    let mut errors = use_signal(Vec::<ConfigError>::new);
+    let mut status = use_signal(String::new);
+    let cmd = use_coroutine_handle::<RitualCmd>();
+
+    let on_save = move |_| {
+        let parsed = form.read().parse();
+        match parsed {
+            Ok(new_config) => {
+                errors.set(Vec::new());
+                let effective = source.read().overrides.apply(new_config.clone());
+                // The workspace saves the current database before moving over to
+                // another one, and writes the config once it has
+                if effective.database() != config.read().database() {
+                    #[cfg(not(feature = "web"))]
+                    if let Err(err) = crate::instance::InstanceLock::acquire(&effective.database())
+                    {
+                        status.set(t!("settings-save-failed", error = err.to_string()));
+                        return;
+                    }
+                    cmd.send(RitualCmd::SwitchDatabase {
+                        config: Box::new(new_config),
+                    });
+                    return;
+                }
+                match new_config.save(&source.read().path) {
+                    Ok(()) => {
+                        if new_config.api != config.read().api
+                            || new_config.history != config.read().history
+                        {
+                            status.set(t!("settings-saved-restart"));
+                        } else {
+                            status.set(t!("settings-saved"));
+                        }
+                        config.set(effective);
+                    }
+                    Err(err) => status.set(t!("settings-save-failed", error = err.to_string())),
+                }
+            }
+            Err(new_errors) => {
+                errors.set(new_errors);
+                status.set(String::new());
+            }
+        }
+    };
+
+    let field_error = move |field: &str| {
+        errors
+            .read()
+            .iter()
+            .find(|e| e.field == field)
+            .map(|e| e.message.clone())
+    };
+    let locale = i18n::locale();
+    let weekdays = std::iter::successors(Some(Weekday::Mon), |d| Some(d.succ())).take(7);
+
+    rsx! {
+        div {
+            class: "main settings",
+            h1 { {t!("settings-title")} }
+            p {
+                class: "config-path",
+                "{source.read().path.display()}"
+            }
+            form {
+                onsubmit: move |e| e.prevent_default(),
+                SettingsField {
+                    label: t!("settings-db-path"),
+                    error: field_error("db_path"),
+                    input {
+                        r#type: "text",
+                        value: "{form.read().db_path}",
+                        oninput: move |e| form.write().db_path = e.data.value(),
+                    }
+                }
+                SettingsField {
+                    label: t!("settings-timezone"),
+                    error: field_error("timezone"),
+                    input {
+                        r#type: "text",
+                        placeholder: t!("settings-timezone-placeholder"),
+                        value: "{form.read().timezone}",
+                        oninput: move |e| form.write().timezone = e.data.value(),
+                    }
+                }
+                SettingsField {
+                    label: t!("settings-day-rollover-hour"),
+                    error: field_error("day_rollover_hour"),
+                    input {
+                        r#type: "number",
+                        min: 0,
+                        max: 23,
+                        value: "{form.read().day_rollover_hour}",
+                        oninput: move |e| form.write().day_rollover_hour = e.data.value(),
+                    }
+                }
+                SettingsField {
+                    label: t!("settings-week-start"),
+                    error: field_error("week_start"),
+                    select {
+                        value: "{form.read().week_start}",
+                        onchange: move |e| form.write().week_start = e.data.value(),
+                        for weekday in weekdays {
+                            option {
+                                value: "{weekday}",
+                                selected: form.read().week_start == weekday.to_string(),
+                                {i18n::weekday_name(locale, weekday)}
+                            }
+                        }
+                    }
+                }
+                SettingsField {
+                    label: t!("settings-theme"),
+                    error: field_error("theme"),
+                    select {
+                        value: "{form.read().theme}",
+                        onchange: move |e| form.write().theme = e.data.value(),
+                        for theme in Theme::ALL {
+                            option {
+                                value: "{theme}",
+                                selected: form.read().theme == theme.to_string(),
+                                {t!(&format!("theme-{theme}"))}
+                            }
+                        }
+                    }
+                }
+                SettingsField {
+                    label: t!("settings-locale"),
+                    error: field_error("locale"),
+                    select {
+                        value: "{form.read().locale}",
+                        onchange: move |e| form.write().locale = e.data.value(),
+                        option {
+                            value: "",
+                            selected: form.read().locale.is_empty(),
+                            {t!("locale-system")}
+                        }
+                        for locale in Locale::ALL {
+                            option {
+                                value: "{locale}",
+                                selected: form.read().locale == locale.to_string(),
+                                "{locale.native_name()}"
+                            }
+                        }
+                    }
+                }
+                SettingsField {
+                    label: t!("settings-autosave-interval"),
+                    error: field_error("autosave_interval"),
+                    input {
+                        r#type: "number",
+                        min: 0,
+                        value: "{form.read().autosave_interval}",
+                        oninput: move |e| form.write().autosave_interval = e.data.value(),
+                    }
+                }
+                h3 { {t!("settings-shortcuts")} }
+                SettingsField {
+                    label: t!("settings-shortcut-new-day"),
+                    error: field_error("shortcuts.new_day"),
+                    input {
+                        r#type: "text",
+                        value: "{form.read().shortcuts.new_day}",
+                        oninput: move |e| form.write().shortcuts.new_day = e.data.value(),
+                    }
+                }
+                SettingsField {
+                    label: t!("settings-shortcut-save"),
+                    error: field_error("shortcuts.save"),
+                    input {
+                        r#type: "text",
+                        value: "{form.read().shortcuts.save}",
+                        oninput: move |e| form.write().shortcuts.save = e.data.value(),
+                    }
+                }
+                SettingsField {
+                    label: t!("settings-shortcut-home"),
+                    error: field_error("shortcuts.home"),
+                    input {
+                        r#type: "text",
+                        value: "{form.read().shortcuts.home}",
+                        oninput: move |e| form.write().shortcuts.home = e.data.value(),
+                    }
+                }
+                SettingsField {
+                    label: t!("settings-shortcut-settings"),
+                    error: field_error("shortcuts.settings"),
+                    input {
+                        r#type: "text",
+                        value: "{form.read().shortcuts.settings}",
+                        oninput: move |e| form.write().shortcuts.settings = e.data.value(),
+                    }
+                }
+                // The API server only exists in native builds
+                if cfg!(not(feature = "web")) {
+                    h3 { {t!("settings-api")} }
+                    SettingsField {
+                        label: t!("settings-api-enabled"),
+                        error: field_error("api.enabled"),
+                        input {
+                            r#type: "checkbox",
+                            checked: form.read().api_enabled,
+                            onchange: move |e| form.write().api_enabled = e.data.checked(),
+                        }
+                    }
+                    SettingsField {
+                        label: t!("settings-api-port"),
+                        error: field_error("api.port"),
+                        input {
+                            r#type: "number",
+                            min: 1,
+                            max: 65535,
+                            value: "{form.read().api_port}",
+                            oninput: move |e| form.write().api_port = e.data.value(),
+                        }
+                    }
+                    SettingsField {
+                        label: t!("settings-api-token"),
+                        error: field_error("api.token"),
+                        input {
+                            r#type: "text",
+                            value: "{form.read().api_token}",
+                            oninput: move |e| form.write().api_token = e.data.value(),
+                        }
+                        Button {
+                            onclick: move |_| form.write().api_token = uuid::Uuid::new_v4().simple().to_string(),
+                            {t!("settings-api-token-generate")}
+                        }
+                    }
+                    SettingsField {
+                        label: t!("settings-history"),
+                        error: field_error("history"),
+                        input {
+                            r#type: "checkbox",
+                            checked: form.read().history,
+                            onchange: move |e| form.write().history = e.data.checked(),
+                        }
+                    }
+                    h3 { {t!("settings-sync")} }
+                    SettingsField {
+                        label: t!("settings-sync-server"),
+                        error: field_error("sync.server"),
+                        input {
+                            r#type: "text",
+                            placeholder: "localhost:7465",
+                            value: "{form.read().sync_server}",
+                            oninput: move |e| form.write().sync_server = e.data.value(),
+                        }
+                    }
+                    SettingsField {
+                        label: t!("settings-sync-interval"),
+                        error: field_error("sync.interval"),
+                        input {
+                            r#type: "number",
+                            min: 0,
+                            value: "{form.read().sync_interval}",
+                            oninput: move |e| form.write().sync_interval = e.data.value(),
+                        }
+                    }
+                    h3 { {t!("settings-daily-notes")} }
+                    SettingsField {
+                        label: t!("settings-daily-notes-folder"),
+                        error: field_error("daily_notes.folder"),
+                        input {
+                            r#type: "text",
+                            value: "{form.read().daily_notes_folder}",
+                            oninput: move |e| form.write().daily_notes_folder = e.data.value(),
+                        }
+                    }
+                    SettingsField {
+                        label: t!("settings-daily-notes-template"),
+                        textarea {
+                            value: "{form.read().daily_notes_template}",
+                            oninput: move |e| form.write().daily_notes_template = e.data.value(),
+                        }
+                    }
+                }
+                Button {
+                    class: "enabled",
+                    r#type: "submit",
+                    onclick: on_save,
+                    {t!("settings-save")}
+                }
+                span { class: "status", "{status}" }
+            }
+        }
+    }
+}
+
+#[component]
+fn SettingsField(label: String, error: Option<String>, children: Element) -> Element {
+    rsx! {
+        label {
+            class: if error.is_some() { "field invalid" } else { "field" },
+            span { class: "label", "{label}" }
+            {children}
+            if let Some(error) = &error {
+                span { class: "error", "{error}" }
+            }
+        }
+    }
+}
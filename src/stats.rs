@@ -0,0 +1,553 @@
+use chrono::{Datelike, NaiveDate, TimeDelta, Weekday};
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::config::Calendar;
+use crate::db::State;
+use crate::types::HabitStatus;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HabitStats {
+    pub habit_id: Uuid,
+    pub title: String,
+    /// Days the habit was on, and the days it was due that were never added
+    pub scheduled: usize,
+    pub done: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Share of the days that [`counts`] that it was done on
+    pub completion_rate: f64,
+    pub current_streak: usize,
+    pub longest_streak: usize,
+}
+
+/// How mood relates to habits, over the days that were rated
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MoodStats {
+    pub rated_days: usize,
+    /// Correlation between mood and the share of a day's habits that were done, from
+    /// -1 to 1. Unknown until there are at least two rated days that differ.
+    pub correlation: Option<f64>,
+    pub habits: Vec<HabitMood>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HabitMood {
+    pub habit_id: Uuid,
+    pub title: String,
+    /// Average mood of the days the habit was done
+    pub when_done: Option<f64>,
+    /// Average mood of the days the habit was on but not done
+    pub when_not_done: Option<f64>,
+}
+
+/// Check-ins of every habit over one week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeekTrend {
+    pub start: NaiveDate,
+    pub done: usize,
+    /// Check-ins that [`counts`]
+    pub counted: usize,
+}
+
+impl WeekTrend {
+    /// Unknown for weeks without anything counted, like paused ones
+    pub fn completion_rate(&self) -> Option<f64> {
+        (self.counted > 0).then(|| self.done as f64 / self.counted as f64)
+    }
+}
+
+/// How a habit went on each day it was on, oldest first. Days it was paused on
+/// are left out.
+pub fn check_ins(
+    state: &State,
+    calendar: &Calendar,
+    habit_id: Uuid,
+) -> Vec<(NaiveDate, HabitStatus)> {
+    state
+        .days
+        .values()
+        .filter_map(|day| {
+            let habit_ref = day.habits.get(&habit_id)?;
+            let date = calendar.date_of(day.date);
+            (!state.is_paused(habit_id, date)).then_some((date, habit_ref.status))
+        })
+        .sorted_by_key(|(date, _)| *date)
+        .collect()
+}
+
+/// Whether a check-in counts towards completion rates, streaks and strength.
+/// Skipped days don't, and neither does today while it is still pending.
+pub fn counts(date: NaiveDate, status: HabitStatus, today: NaiveDate) -> bool {
+    status != HabitStatus::Skipped && !(date == today && status == HabitStatus::Pending)
+}
+
+/// `check_ins` with a miss on each day before `end` that the habit was `due` on but
+/// that has no check-in, starting from the first one
+pub fn with_misses(
+    check_ins: &[(NaiveDate, HabitStatus)],
+    due: impl Fn(NaiveDate) -> bool,
+    end: NaiveDate,
+) -> Vec<(NaiveDate, HabitStatus)> {
+    let Some(&(first, _)) = check_ins.first() else {
+        return Vec::new();
+    };
+    let recorded: HashSet<NaiveDate> = check_ins.iter().map(|(date, _)| *date).collect();
+    let misses = first
+        .iter_days()
+        .take_while(|date| *date < end)
+        .filter(|date| !recorded.contains(date) && due(*date))
+        .map(|date| (date, HabitStatus::Failed));
+    check_ins
+        .iter()
+        .copied()
+        .chain(misses)
+        .sorted_by_key(|(date, _)| *date)
+        .collect()
+}
+
+/// The check-ins of a habit with a miss on each day until `today` that it was due
+/// on but that was never added, so a habit fades while the app goes unused too
+pub fn tracked_check_ins(
+    state: &State,
+    calendar: &Calendar,
+    habit_id: Uuid,
+    today: NaiveDate,
+) -> Vec<(NaiveDate, HabitStatus)> {
+    let Some(habit) = state.habits.get(&habit_id) else {
+        return Vec::new();
+    };
+    let check_ins = check_ins(state, calendar, habit_id);
+    // An archived habit is no longer due, so nothing is missed after it
+    let end = match check_ins.last() {
+        Some((last, _)) if habit.archived => *last,
+        _ => today,
+    };
+    let due = |date| habit.schedule.includes(date) && !state.is_paused(habit_id, date);
+    with_misses(&check_ins, due, end)
+}
+
+/// Consecutive done check-ins counting back from the latest one, out of those that
+/// [`counts`]
+pub fn current_streak(check_ins: &[(NaiveDate, HabitStatus)], today: NaiveDate) -> usize {
+    check_ins
+        .iter()
+        .rev()
+        .filter(|(date, status)| counts(*date, *status, today))
+        .take_while(|(_, status)| *status == HabitStatus::Done)
+        .count()
+}
+
+pub fn longest_streak(check_ins: &[(NaiveDate, HabitStatus)]) -> usize {
+    check_ins
+        .iter()
+        .filter(|(_, status)| *status != HabitStatus::Skipped)
+        .chunk_by(|(_, status)| *status == HabitStatus::Done)
+        .into_iter()
+        .filter(|(done, _)| *done)
+        .map(|(_, run)| run.count())
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn habit_stats(state: &State, calendar: &Calendar, today: NaiveDate) -> Vec<HabitStats> {
+    state
+        .habits
+        .values()
+        .map(|habit| {
+            let check_ins = tracked_check_ins(state, calendar, habit.id, today);
+            let count = |wanted: HabitStatus| {
+                check_ins
+                    .iter()
+                    .filter(|(_, status)| *status == wanted)
+                    .count()
+            };
+            let done = count(HabitStatus::Done);
+            let counted = check_ins
+                .iter()
+                .filter(|(date, status)| counts(*date, *status, today))
+                .count();
+            HabitStats {
+                habit_id: habit.id,
+                title: habit.title.clone(),
+                scheduled: check_ins.len(),
+                done,
+                skipped: count(HabitStatus::Skipped),
+                failed: count(HabitStatus::Failed),
+                completion_rate: if counted == 0 {
+                    0.0
+                } else {
+                    done as f64 / counted as f64
+                },
+                current_streak: current_streak(&check_ins, today),
+                longest_streak: longest_streak(&check_ins),
+            }
+        })
+        .collect()
+}
+
+/// The [tracked check-ins](tracked_check_ins) of every habit, oldest first
+pub fn all_check_ins(
+    state: &State,
+    calendar: &Calendar,
+    today: NaiveDate,
+) -> Vec<(NaiveDate, HabitStatus)> {
+    state
+        .habits
+        .keys()
+        .flat_map(|id| tracked_check_ins(state, calendar, *id, today))
+        .sorted_by_key(|(date, _)| *date)
+        .collect()
+}
+
+/// One entry per week from the first check-in to the last, including the weeks
+/// without any
+pub fn weekly(
+    check_ins: &[(NaiveDate, HabitStatus)],
+    week_start: Weekday,
+    today: NaiveDate,
+) -> Vec<WeekTrend> {
+    let week_of =
+        |date: NaiveDate| date - TimeDelta::days(date.weekday().days_since(week_start).into());
+    let (Some((first, _)), Some((last, _))) = (check_ins.first(), check_ins.last()) else {
+        return Vec::new();
+    };
+    let mut weeks = std::iter::successors(Some(week_of(*first)), |start| {
+        Some(*start + TimeDelta::weeks(1)).filter(|start| *start <= *last)
+    })
+    .map(|start| WeekTrend {
+        start,
+        done: 0,
+        counted: 0,
+    })
+    .collect::<Vec<_>>();
+    let first_week = weeks[0].start;
+    for (date, status) in check_ins {
+        let week = &mut weeks[((week_of(*date) - first_week).num_weeks()) as usize];
+        if counts(*date, *status, today) {
+            week.counted += 1;
+            if *status == HabitStatus::Done {
+                week.done += 1;
+            }
+        }
+    }
+    weeks
+}
+
+/// Done check-ins on each day of the week, starting with `week_start`
+pub fn by_weekday(
+    check_ins: &[(NaiveDate, HabitStatus)],
+    week_start: Weekday,
+) -> Vec<(Weekday, usize)> {
+    std::iter::successors(Some(week_start), |weekday| Some(weekday.succ()))
+        .take(7)
+        .map(|weekday| {
+            let done = check_ins
+                .iter()
+                .filter(|(date, status)| date.weekday() == weekday && *status == HabitStatus::Done)
+                .count();
+            (weekday, done)
+        })
+        .collect()
+}
+
+/// Pearson correlation of the pairs, if neither side is constant
+pub fn correlation(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let (mean_x, mean_y) = (
+        pairs.iter().map(|(x, _)| x).sum::<f64>() / n,
+        pairs.iter().map(|(_, y)| y).sum::<f64>() / n,
+    );
+    let (mut covariance, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        covariance += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    (var_x > 0.0 && var_y > 0.0).then(|| covariance / (var_x * var_y).sqrt())
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+pub fn mood_stats(state: &State, calendar: &Calendar) -> MoodStats {
+    let rated = state
+        .days
+        .values()
+        .filter_map(|day| Some((day, f64::from(day.mood?))))
+        .collect::<Vec<_>>();
+    let counted = |day: &crate::types::Day| {
+        let date = calendar.date_of(day.date);
+        day.habits
+            .values()
+            .filter(move |habit_ref| {
+                habit_ref.status != HabitStatus::Skipped && !state.is_paused(habit_ref.id, date)
+            })
+            .map(|habit_ref| (habit_ref.id, habit_ref.status))
+            .collect::<Vec<_>>()
+    };
+    let completion = rated
+        .iter()
+        .filter_map(|(day, mood)| {
+            let statuses = counted(day);
+            let done = statuses
+                .iter()
+                .filter(|(_, status)| *status == HabitStatus::Done)
+                .count();
+            (!statuses.is_empty()).then(|| (*mood, done as f64 / statuses.len() as f64))
+        })
+        .collect::<Vec<_>>();
+    let habits = state
+        .habits
+        .values()
+        .map(|habit| {
+            let moods = rated
+                .iter()
+                .filter_map(|(day, mood)| {
+                    let (_, status) = counted(day).into_iter().find(|(id, _)| *id == habit.id)?;
+                    Some((status == HabitStatus::Done, *mood))
+                })
+                .collect::<Vec<_>>();
+            let average = |done: bool| {
+                mean(
+                    moods
+                        .iter()
+                        .filter(|(was_done, _)| *was_done == done)
+                        .map(|(_, mood)| *mood),
+                )
+            };
+            HabitMood {
+                habit_id: habit.id,
+                title: habit.title.clone(),
+                when_done: average(true),
+                when_not_done: average(false),
+            }
+        })
+        .collect();
+    MoodStats {
+        rated_days: rated.len(),
+        correlation: correlation(&completion),
+        habits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::parse_check_ins;
+
+    #[test]
+    fn streaks() {
+        let check_ins = parse_check_ins("dddfdd");
+        let today = check_ins.last().unwrap().0;
+        assert_eq!(current_streak(&check_ins, today), 2);
+        assert_eq!(longest_streak(&check_ins), 3);
+        assert_eq!(longest_streak(&[]), 0);
+    }
+
+    #[test]
+    fn pending_today_keeps_streak() {
+        let check_ins = parse_check_ins("ddp");
+        let today = check_ins.last().unwrap().0;
+        assert_eq!(current_streak(&check_ins, today), 2);
+        assert_eq!(current_streak(&check_ins, today.succ_opt().unwrap()), 0);
+    }
+
+    #[test]
+    fn skipped_days_are_neutral() {
+        let check_ins = parse_check_ins("ddsdsd");
+        let today = check_ins.last().unwrap().0;
+        assert_eq!(current_streak(&check_ins, today), 4);
+        assert_eq!(longest_streak(&check_ins), 4);
+        assert_eq!(longest_streak(&parse_check_ins("ddfsd")), 2);
+    }
+
+    #[test]
+    fn weekly_trends() {
+        // 2025-03-01 is a Saturday
+        let march = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        let weeks = weekly(&parse_check_ins("ddfsdddp"), Weekday::Mon, march(8));
+        let starts = weeks
+            .iter()
+            .map(|week| week.start.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(starts, ["2025-02-24", "2025-03-03"]);
+        assert_eq!((weeks[0].done, weeks[0].counted), (2, 2));
+        assert_eq!((weeks[1].done, weeks[1].counted), (3, 4));
+        assert_eq!(weeks[1].completion_rate(), Some(0.75));
+        assert_eq!(
+            weekly(&parse_check_ins("d"), Weekday::Sat, march(1))[0]
+                .start
+                .to_string(),
+            "2025-03-01"
+        );
+
+        // Weeks in between without check-ins are kept
+        let mut gap = parse_check_ins("d");
+        gap.push((march(20), HabitStatus::Failed));
+        let weeks = weekly(&gap, Weekday::Mon, march(20));
+        assert_eq!(weeks.len(), 4);
+        assert_eq!(weeks[2].completion_rate(), None);
+        assert!(weekly(&[], Weekday::Mon, march(20)).is_empty());
+        // Only today's pending check-in is left out, an earlier one was missed
+        let weeks = weekly(&parse_check_ins("dp"), Weekday::Sat, march(3));
+        assert_eq!((weeks[0].done, weeks[0].counted), (1, 2));
+    }
+
+    #[test]
+    fn fills_in_missing_days() {
+        // The 1st is a Saturday, the 3rd to the 5th were never added
+        let check_ins = parse_check_ins("dd");
+        let end = NaiveDate::from_ymd_opt(2025, 3, 9).unwrap();
+        let statuses = |check_ins: Vec<(NaiveDate, HabitStatus)>| {
+            check_ins
+                .iter()
+                .map(|(date, status)| format!("{}{status:?}", date.day()))
+                .join(" ")
+        };
+        assert_eq!(
+            statuses(with_misses(&check_ins, |_| true, end)),
+            "1Done 2Done 3Failed 4Failed 5Failed 6Failed 7Failed 8Failed"
+        );
+        let weekend = |date: NaiveDate| matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        assert_eq!(
+            statuses(with_misses(&check_ins, weekend, end)),
+            "1Done 2Done 8Failed"
+        );
+        assert!(with_misses(&[], |_| true, end).is_empty());
+    }
+
+    #[test]
+    fn stats_agree_on_what_counts() {
+        use crate::utils::{NonEmpty, Validate};
+
+        let calendar = Calendar {
+            timezone: Some(chrono_tz::UTC),
+            rollover_hour: 0,
+        };
+        let now = chrono::Utc::now();
+        let today = calendar.date_of(now);
+        let mut state = State::default();
+        // Done, done, never added, skipped, done, never added and pending today
+        let days = [
+            (6, HabitStatus::Done),
+            (5, HabitStatus::Done),
+            (3, HabitStatus::Skipped),
+            (2, HabitStatus::Done),
+            (0, HabitStatus::Pending),
+        ];
+        for (days_ago, _) in days {
+            state
+                .add_day(now - TimeDelta::days(days_ago), &calendar)
+                .unwrap();
+            if state.habits.is_empty() {
+                let day_id = *state.days.keys().next().unwrap();
+                let title = NonEmpty::new_validated("Run".to_string()).unwrap();
+                state.add_habit_to_day(title, day_id).unwrap();
+            }
+        }
+        let habit_id = *state.habits.keys().next().unwrap();
+        for (days_ago, status) in days {
+            let day_id = state
+                .day_on(today - TimeDelta::days(days_ago), &calendar)
+                .unwrap()
+                .id;
+            state.set_habit_status(day_id, habit_id, status).unwrap();
+        }
+
+        // Five days count, three of them done
+        let stats = &habit_stats(&state, &calendar, today)[0];
+        assert_eq!((stats.scheduled, stats.skipped, stats.failed), (7, 1, 2));
+        assert!((stats.completion_rate - 0.6).abs() < 1e-9);
+        assert_eq!((stats.current_streak, stats.longest_streak), (0, 2));
+        let weeks = weekly(
+            &all_check_ins(&state, &calendar, today),
+            Weekday::Mon,
+            today,
+        );
+        let total = |count: fn(&WeekTrend) -> usize| weeks.iter().map(count).sum::<usize>();
+        assert_eq!(
+            (total(|week| week.done), total(|week| week.counted)),
+            (3, 5)
+        );
+        let strength = crate::strength::habit_history(&state, &calendar, habit_id);
+        assert_eq!(strength.len(), 5);
+    }
+
+    #[test]
+    fn weekday_distribution() {
+        let days = by_weekday(&parse_check_ins("ddfsdddddd"), Weekday::Sun);
+        assert_eq!(days[0], (Weekday::Sun, 2));
+        assert_eq!(days[1], (Weekday::Mon, 1));
+        assert_eq!(days[6], (Weekday::Sat, 2));
+        assert_eq!(days.iter().map(|(_, done)| done).sum::<usize>(), 8);
+    }
+
+    #[test]
+    fn mood_correlation() {
+        let rising = [(1.0, 0.0), (3.0, 0.5), (5.0, 1.0)];
+        assert!((correlation(&rising).unwrap() - 1.0).abs() < 1e-9);
+        let falling = [(1.0, 1.0), (5.0, 0.0)];
+        assert!((correlation(&falling).unwrap() + 1.0).abs() < 1e-9);
+        assert_eq!(correlation(&[(2.0, 0.5), (4.0, 0.5)]), None);
+        assert_eq!(correlation(&[(2.0, 0.5)]), None);
+    }
+
+    #[test]
+    fn paused_days_are_left_out() {
+        use crate::types::Pause;
+        use crate::utils::{NonEmpty, Validate};
+
+        let calendar = Calendar {
+            timezone: Some(chrono_tz::UTC),
+            rollover_hour: 0,
+        };
+        let start = "2025-03-01T12:00:00Z"
+            .parse::<crate::types::DateTime>()
+            .unwrap();
+        let mut state = State::default();
+        state.add_day(start, &calendar).unwrap();
+        let first_day = *state.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Run".to_string()).unwrap();
+        state.add_habit_to_day(title, first_day).unwrap();
+        let habit_id = *state.habits.keys().next().unwrap();
+        for offset in 1..3 {
+            state
+                .add_day(start + TimeDelta::days(offset), &calendar)
+                .unwrap();
+        }
+        // Done, failed while paused, done
+        for (day_id, status) in state
+            .days
+            .values()
+            .sorted_by_key(|day| day.date)
+            .map(|day| day.id)
+            .zip([HabitStatus::Done, HabitStatus::Failed, HabitStatus::Done])
+            .collect::<Vec<_>>()
+        {
+            state.set_habit_status(day_id, habit_id, status).unwrap();
+        }
+        let paused = NaiveDate::from_ymd_opt(2025, 3, 2).unwrap();
+        state
+            .add_pause(Pause::new(paused, paused, Some(habit_id)))
+            .unwrap();
+
+        let dates = check_ins(&state, &calendar, habit_id)
+            .into_iter()
+            .map(|(date, _)| date.day())
+            .collect::<Vec<_>>();
+        assert_eq!(dates, [1, 3]);
+        let today = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        assert_eq!(all_check_ins(&state, &calendar, today).len(), 2);
+        let stats = &habit_stats(&state, &calendar, today)[0];
+        assert_eq!((stats.scheduled, stats.done, stats.failed), (2, 2, 0));
+        assert_eq!((stats.current_streak, stats.longest_streak), (2, 2));
+    }
+}
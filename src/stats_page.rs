@@ -0,0 +1,123 @@
+use dioxus::prelude::*;
+
+use crate::components::charts::{BarChart, LineChart};
+use crate::config::Config;
+use crate::db::State;
+use crate::i18n::{self, t};
+use crate::stats;
+
+/// How many weeks back the trend charts go
+const TREND_WEEKS: usize = 26;
+
+fn percent(share: f64) -> String {
+    format!("{:.0}%", share * 100.0)
+}
+
+fn mood(mood: Option<f64>) -> String {
+    mood.map(|mood| format!("{:.1}", mood)).unwrap_or_default()
+}
+
+#[component]
+pub fn Stats() -> Element {
+    let state = use_context::<Signal<State>>();
+    let config = use_context::<Signal<Config>>();
+    let calendar = config.read().calendar();
+    let today = calendar.today();
+    let habits = stats::habit_stats(&state.read(), &calendar, today);
+    let moods = stats::mood_stats(&state.read(), &calendar);
+    let week_start = config.read().week_start;
+    let check_ins = stats::all_check_ins(&state.read(), &calendar, today);
+    let weeks = stats::weekly(&check_ins, week_start, today);
+    let weeks = &weeks[weeks.len().saturating_sub(TREND_WEEKS)..];
+    let completion = weeks
+        .iter()
+        .map(stats::WeekTrend::completion_rate)
+        .collect::<Vec<_>>();
+    let per_week = weeks
+        .iter()
+        .map(|week| week.done as f64)
+        .collect::<Vec<_>>();
+    let (weekdays, per_weekday): (Vec<_>, Vec<_>) = stats::by_weekday(&check_ins, week_start)
+        .into_iter()
+        .map(|(weekday, done)| (i18n::weekday_initial(i18n::locale(), weekday), done as f64))
+        .unzip();
+    let correlation = match moods.correlation {
+        Some(correlation) if correlation >= 0.3 => t!("stats-mood-positive"),
+        Some(correlation) if correlation <= -0.3 => t!("stats-mood-negative"),
+        Some(_) => t!("stats-mood-none"),
+        None => t!("stats-mood-unknown"),
+    };
+
+    rsx! {
+        div {
+            class: "main stats",
+            h1 { {t!("stats-title")} }
+            table {
+                thead {
+                    tr {
+                        th { {t!("stats-habit")} }
+                        th { {t!("stats-done")} }
+                        th { {t!("stats-completion")} }
+                        th { {t!("stats-current-streak")} }
+                        th { {t!("stats-longest-streak")} }
+                    }
+                }
+                tbody {
+                    for habit in habits {
+                        tr {
+                            key: "{habit.habit_id}",
+                            td { "{habit.title}" }
+                            td { "{habit.done}/{habit.scheduled}" }
+                            td { {percent(habit.completion_rate)} }
+                            td { "{habit.current_streak}" }
+                            td { "{habit.longest_streak}" }
+                        }
+                    }
+                }
+            }
+            if !weeks.is_empty() {
+                h2 { {t!("stats-trends-title")} }
+                div {
+                    class: "trends",
+                    figure {
+                        figcaption { {t!("stats-trend-completion")} }
+                        LineChart { values: completion }
+                    }
+                    figure {
+                        figcaption { {t!("stats-trend-per-week")} }
+                        BarChart { values: per_week }
+                    }
+                    figure {
+                        figcaption { {t!("stats-trend-per-weekday")} }
+                        BarChart { values: per_weekday, labels: weekdays }
+                    }
+                }
+            }
+            h2 { {t!("stats-mood-title")} }
+            p {
+                {t!("stats-mood-rated", days = moods.rated_days)}
+                " "
+                {correlation}
+            }
+            table {
+                thead {
+                    tr {
+                        th { {t!("stats-habit")} }
+                        th { {t!("stats-mood-when-done")} }
+                        th { {t!("stats-mood-when-not-done")} }
+                    }
+                }
+                tbody {
+                    for habit in moods.habits {
+                        tr {
+                            key: "{habit.habit_id}",
+                            td { "{habit.title}" }
+                            td { {mood(habit.when_done)} }
+                            td { {mood(habit.when_not_done)} }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
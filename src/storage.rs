@@ -0,0 +1,88 @@
+//! Persistence for the database and config files.
+//!
+//! Natively these are plain files. In the browser there is no filesystem, so the
+//! same paths are used as keys into `localStorage` instead.
+
+use std::path::Path;
+
+use crate::prelude::*;
+
+#[cfg(not(feature = "web"))]
+mod backend {
+    use super::*;
+
+    pub fn exists(path: &Path) -> bool {
+        path.exists()
+    }
+
+    pub fn read_to_string(path: &Path) -> Result<String> {
+        Ok(std::fs::read_to_string(path)?)
+    }
+
+    pub fn write(path: &Path, contents: &str) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Written next to the file and moved over it, so that a crash halfway never
+        // leaves it truncated
+        let temp = crate::utils::sibling_path(path, "tmp");
+        std::fs::write(&temp, contents)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "web")]
+mod backend {
+    use super::*;
+    use color_eyre::eyre;
+
+    fn local_storage() -> Result<web_sys::Storage> {
+        web_sys::window()
+            .ok_or_else(|| eyre::eyre!("No browser window"))?
+            .local_storage()
+            .map_err(|err| eyre::eyre!("Failed to access localStorage: {:?}", err))?
+            .ok_or_else(|| eyre::eyre!("localStorage is not available"))
+    }
+
+    fn key(path: &Path) -> String {
+        path.to_string_lossy().into_owned()
+    }
+
+    pub fn exists(path: &Path) -> bool {
+        local_storage()
+            .ok()
+            .and_then(|storage| storage.get_item(&key(path)).ok().flatten())
+            .is_some()
+    }
+
+    pub fn read_to_string(path: &Path) -> Result<String> {
+        local_storage()?
+            .get_item(&key(path))
+            .map_err(|err| eyre::eyre!("Failed to read {:?}: {:?}", path, err))?
+            .ok_or_else(|| eyre::eyre!("{:?} not found in localStorage", path))
+    }
+
+    pub fn write(path: &Path, contents: &str) -> Result<()> {
+        local_storage()?
+            .set_item(&key(path), contents)
+            .map_err(|err| eyre::eyre!("Failed to write {:?}: {:?}", path, err))
+    }
+}
+
+pub use backend::{exists, read_to_string, write};
+
+#[cfg(all(test, not(feature = "web")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_replaces_whole_file() {
+        let dir = std::env::temp_dir().join(format!("ritual-storage-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("db.json");
+        write(&path, "a longer first version").unwrap();
+        write(&path, "second").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "second");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    }
+}
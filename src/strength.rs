@@ -0,0 +1,165 @@
+//! How well established a habit is, as a score from 0 to 1.
+//!
+//! Like Loop Habit Tracker, the score is an exponential moving average of the
+//! check-ins, so a single miss only dents a long run instead of resetting it the
+//! way a streak does. A daily habit that is no longer done loses half its strength
+//! in 13 days. Habits on fewer days a week fade more slowly, over 13 days divided
+//! by the square root of their frequency, so about 34 days for a weekly one.
+//! Scheduled days that were never added count as misses, so a habit fades while
+//! the app goes unused too.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::config::Calendar;
+use crate::db::State;
+use crate::stats;
+use crate::types::HabitStatus;
+
+/// Days for a daily habit's strength to halve
+const HALF_LIFE_DAYS: f64 = 13.0;
+
+/// How much of the score carries over from one check-in to the next, for a habit
+/// on `days_per_week` days
+pub fn multiplier(days_per_week: usize) -> f64 {
+    let frequency = days_per_week.clamp(1, 7) as f64 / 7.0;
+    0.5f64.powf(1.0 / (HALF_LIFE_DAYS * frequency.sqrt()))
+}
+
+/// The score after each check-in, oldest first. Check-ins that don't
+/// [count](stats::counts) leave it as it is.
+pub fn history(
+    check_ins: &[(NaiveDate, HabitStatus)],
+    days_per_week: usize,
+    today: NaiveDate,
+) -> Vec<(NaiveDate, f64)> {
+    let multiplier = multiplier(days_per_week);
+    check_ins
+        .iter()
+        .filter(|(date, status)| stats::counts(*date, *status, today))
+        .scan(0.0, |score, (date, status)| {
+            let value = if *status == HabitStatus::Done {
+                1.0
+            } else {
+                0.0
+            };
+            *score = *score * multiplier + value * (1.0 - multiplier);
+            Some((*date, *score))
+        })
+        .collect()
+}
+
+/// The current score of every habit, 0 for those never on
+pub fn strengths(state: &State, calendar: &Calendar) -> HashMap<Uuid, f64> {
+    state
+        .habits
+        .keys()
+        .map(|id| {
+            let score = habit_history(state, calendar, *id)
+                .last()
+                .map_or(0.0, |(_, score)| *score);
+            (*id, score)
+        })
+        .collect()
+}
+
+pub fn habit_history(state: &State, calendar: &Calendar, habit_id: Uuid) -> Vec<(NaiveDate, f64)> {
+    let Some(habit) = state.habits.get(&habit_id) else {
+        return Vec::new();
+    };
+    let today = calendar.today();
+    history(
+        &stats::tracked_check_ins(state, calendar, habit_id, today),
+        habit.schedule.weekdays.len(),
+        today,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::parse_check_ins;
+
+    fn score(statuses: &str, days_per_week: usize) -> f64 {
+        let check_ins = parse_check_ins(statuses);
+        let today = check_ins.last().unwrap().0;
+        history(&check_ins, days_per_week, today)
+            .last()
+            .map_or(0.0, |(_, score)| *score)
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn half_life() {
+        // Thirteen days in a row get a daily habit halfway there
+        assert_close(score(&"d".repeat(13), 7), 0.5);
+        assert_close(score(&"d".repeat(26), 7), 0.75);
+        // And thirteen days of misses take half of it away again
+        let full = score(&"d".repeat(13), 7);
+        assert_close(
+            score(&format!("{}{}", "d".repeat(13), "f".repeat(13)), 7),
+            full / 2.0,
+        );
+    }
+
+    #[test]
+    fn respects_frequency() {
+        // A weekly habit's 34 day half-life is 13 × √(1/7) check-ins
+        assert_close(score(&"d".repeat(13), 1), 1.0 - 0.5f64.powf(7f64.sqrt()));
+        assert!(score("ddd", 1) > score("ddd", 7));
+        assert_close(multiplier(0), multiplier(1));
+    }
+
+    #[test]
+    fn single_miss_is_a_dent() {
+        let steady = score(&"d".repeat(30), 7);
+        let missed = score(&format!("{}f{}", "d".repeat(15), "d".repeat(14)), 7);
+        assert!(missed < steady);
+        assert!(missed > steady * 0.9);
+    }
+
+    #[test]
+    fn skipped_and_pending_today_are_neutral() {
+        assert_close(score("ddsdd", 7), score("dddd", 7));
+        assert_close(score("ddddp", 7), score("dddd", 7));
+        assert!(score("ddddpd", 7) < score("ddddd", 7));
+    }
+
+    #[test]
+    fn fades_while_unused() {
+        use crate::utils::{NonEmpty, Validate};
+        let calendar = Calendar {
+            timezone: Some(chrono_tz::UTC),
+            rollover_hour: 0,
+        };
+        let mut state = State::default();
+        let now = chrono::Utc::now();
+        state
+            .add_day(now - chrono::TimeDelta::days(3), &calendar)
+            .unwrap();
+        let day_id = *state.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Run".to_string()).unwrap();
+        state.add_habit_to_day(title, day_id).unwrap();
+        let run = *state.habits.keys().next().unwrap();
+        state
+            .set_habit_status(day_id, run, HabitStatus::Done)
+            .unwrap();
+
+        // The two days since count as misses, today is still open
+        let done_once = score("d", 7);
+        assert_close(
+            strengths(&state, &calendar)[&run],
+            done_once * multiplier(7).powi(2),
+        );
+        state.set_habit_archived(run, true).unwrap();
+        assert_close(strengths(&state, &calendar)[&run], done_once);
+    }
+}
@@ -0,0 +1,764 @@
+//! Syncing the database between devices.
+//!
+//! Each replica keeps a log of operations next to its database. Local edits are
+//! turned into operations by diffing the state against what was last recorded, and
+//! operations from other replicas are merged last-writer-wins per day, habit,
+//! habit of a day, journal of a day and pause, so every replica ends up with the
+//! same state whatever the order they sync in. Nothing can be deleted yet, so there
+//! are no tombstones. When two replicas each add a day for the same date, both end
+//! up merging it into the day with the lowest id, like merging files does, and
+//! changes to the other day apply to that one from then on.
+//!
+//! Once the server has an operation that another one superseded, neither replica
+//! needs it anymore, so the log only keeps the operations still in effect after
+//! each sync. When the database is encrypted, the server only ever sees sealed
+//! payloads, and plain ones are refused so it can't slip in changes of its own.
+
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::config::Calendar;
+use crate::crypto::{self, Cipher, SyncKeys, SALT_LEN};
+use crate::db::State;
+use crate::prelude::*;
+use crate::storage;
+use crate::types::{DateTime, Habit, HabitRef, HabitStatus, Pause};
+use crate::utils::{self, differs};
+
+pub mod protocol;
+
+use protocol::{ClientMessage, Op, ServerMessage, VectorClock};
+
+/// How long to wait for the server before giving up
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Change {
+    Day {
+        id: Uuid,
+        date: DateTime,
+    },
+    Habit(Habit),
+    HabitRef {
+        day_id: Uuid,
+        habit: HabitRef,
+    },
+    Journal {
+        day_id: Uuid,
+        journal: Option<String>,
+        mood: Option<u8>,
+    },
+    Pause(Pause),
+}
+
+/// What a change overwrites, the unit that last-writer-wins applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Key {
+    Day(Uuid),
+    Habit(Uuid),
+    HabitRef(Uuid, Uuid),
+    Journal(Uuid),
+    Pause(Uuid),
+}
+
+/// The day that the day with `id` was merged into, following `aliases`
+fn canonical(aliases: &HashMap<Uuid, Uuid>, mut id: Uuid) -> Uuid {
+    while let Some(&into) = aliases.get(&id) {
+        id = into;
+    }
+    id
+}
+
+impl Change {
+    fn key(&self, aliases: &HashMap<Uuid, Uuid>) -> Key {
+        match self {
+            Change::Day { id, .. } => Key::Day(canonical(aliases, *id)),
+            Change::Habit(habit) => Key::Habit(habit.id),
+            Change::HabitRef { day_id, habit } => {
+                Key::HabitRef(canonical(aliases, *day_id), habit.id)
+            }
+            Change::Journal { day_id, .. } => Key::Journal(canonical(aliases, *day_id)),
+            Change::Pause(pause) => Key::Pause(pause.id),
+        }
+    }
+
+    fn apply(&self, state: &mut State, aliases: &HashMap<Uuid, Uuid>) {
+        match self {
+            Change::Day { id, date } => state.days.set_date(canonical(aliases, *id), *date),
+            Change::Habit(habit) => {
+                state.habits.insert(habit.id, habit.clone());
+            }
+            Change::HabitRef { day_id, habit } => {
+                match state.days.get_mut(&canonical(aliases, *day_id)) {
+                    Some(day) => {
+                        day.habits.insert(habit.id, habit.clone());
+                    }
+                    // Days are always created before their habits, so this means the
+                    // operations were not delivered in causal order
+                    None => warn!("Dropping habit {} of unknown day {}", habit.id, day_id),
+                }
+            }
+            Change::Journal {
+                day_id,
+                journal,
+                mood,
+            } => match state.days.get_mut(&canonical(aliases, *day_id)) {
+                Some(day) => {
+                    day.journal = journal.clone();
+                    day.mood = *mood;
+                }
+                None => warn!("Dropping journal of unknown day {}", day_id),
+            },
+            Change::Pause(pause) => {
+                state.pauses.insert(pause.id, pause.clone());
+            }
+        }
+    }
+}
+
+/// The changes that turn `base` into `state`
+pub fn diff(base: &State, state: &State) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for habit in state.habits.values() {
+        if base
+            .habits
+            .get(&habit.id)
+            .is_none_or(|old| differs(old, habit))
+        {
+            changes.push(Change::Habit(habit.clone()));
+        }
+    }
+    for day in state.days.values() {
+        let old = base.days.get(&day.id);
+        if old.is_none_or(|old| old.date != day.date) {
+            changes.push(Change::Day {
+                id: day.id,
+                date: day.date,
+            });
+        }
+        let (journal, mood) = old.map_or((None, None), |old| (old.journal.as_ref(), old.mood));
+        if journal != day.journal.as_ref() || mood != day.mood {
+            changes.push(Change::Journal {
+                day_id: day.id,
+                journal: day.journal.clone(),
+                mood: day.mood,
+            });
+        }
+        for habit in day.habits.values() {
+            let old = old.and_then(|old| old.habits.get(&habit.id));
+            if old.is_none_or(|old| differs(old, habit)) {
+                changes.push(Change::HabitRef {
+                    day_id: day.id,
+                    habit: habit.clone(),
+                });
+            }
+        }
+    }
+    for pause in state.pauses.values() {
+        if base.pauses.get(&pause.id) != Some(pause) {
+            changes.push(Change::Pause(pause.clone()));
+        }
+    }
+    changes
+}
+
+/// Whether `op` takes over from `current`, an operation with the same key. Of two
+/// concurrent check-ins, one that a new day merely started out with loses to an
+/// edited one, so a day that two replicas added keeps what was done on either.
+fn wins(op: &Op<Change>, current: &Op<Change>) -> bool {
+    let untouched = |change: &Change| {
+        matches!(change, Change::HabitRef { habit, .. }
+            if habit.status == HabitStatus::Pending && habit.note.is_none())
+    };
+    let concurrent =
+        !op.clock.happened_before(&current.clock) && !current.clock.happened_before(&op.clock);
+    match (untouched(&op.change), untouched(&current.change)) {
+        (true, false) if concurrent => false,
+        (false, true) if concurrent => true,
+        _ => op.supersedes(current),
+    }
+}
+
+/// Where the sync log of the database at `db_path` is kept
+pub fn log_path(db_path: &Path) -> PathBuf {
+    utils::sibling_path(db_path, "sync")
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Log {
+    replica: Uuid,
+    clock: VectorClock,
+    ops: Vec<Op<Change>>,
+    /// Salt of the key this replica seals its payloads with
+    #[serde(default = "crypto::random_salt")]
+    salt: [u8; SALT_LEN],
+    /// Days added for a date that already had one, and the day they were merged
+    /// into
+    #[serde(default)]
+    aliases: HashMap<Uuid, Uuid>,
+    /// The state as of the last recorded or merged operation, rebuilt from the
+    /// operations in effect when opening
+    #[serde(skip)]
+    base: State,
+}
+
+/// What is sent in place of a change, sealed when the database is encrypted
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Payload {
+    Sealed { sealed: String },
+    Plain(Change),
+}
+
+impl Payload {
+    fn seal(change: Change, keys: Option<&mut SyncKeys>) -> Result<Self> {
+        Ok(match keys {
+            Some(keys) => Payload::Sealed {
+                sealed: keys.seal(&serde_json::to_string(&change)?)?,
+            },
+            None => Payload::Plain(change),
+        })
+    }
+
+    fn open(self, keys: Option<&mut SyncKeys>) -> Result<Change> {
+        match (self, keys) {
+            (Payload::Plain(change), None) => Ok(change),
+            (Payload::Plain(_), Some(_)) => Err(eyre::eyre!(
+                "Received unencrypted changes, only encrypted ones are accepted for an encrypted database"
+            )),
+            (Payload::Sealed { sealed }, Some(keys)) => {
+                Ok(serde_json::from_str(&keys.open(&sealed)?)?)
+            }
+            (Payload::Sealed { .. }, None) => Err(eyre::eyre!(
+                "Received encrypted changes, encrypt this database with the same passphrase to sync it"
+            )),
+        }
+    }
+}
+
+/// This device's copy of the operation log
+#[derive(Debug)]
+pub struct Replica {
+    path: PathBuf,
+    log: Log,
+    /// Index of the operation currently in effect for each key
+    winners: HashMap<Key, usize>,
+    /// The log holds a copy of the database, so it is encrypted along with it
+    cipher: Option<Cipher>,
+}
+
+impl Replica {
+    /// Opens the log of the database at `db_path`, starting a new replica if there
+    /// is none
+    pub fn open(db_path: &Path, cipher: Option<Cipher>) -> Result<Self> {
+        let path = log_path(db_path);
+        let log = if storage::exists(&path) {
+            let contents = storage::read_to_string(&path)?;
+            serde_json::from_str(&crypto::decode(&contents, cipher.as_ref())?)?
+        } else {
+            let replica = Uuid::new_v4();
+            info!("Starting sync replica {} for {:?}", replica, db_path);
+            Log {
+                replica,
+                clock: VectorClock::default(),
+                ops: Vec::new(),
+                salt: crypto::random_salt(),
+                aliases: HashMap::new(),
+                base: State::default(),
+            }
+        };
+        let mut replica = Self {
+            path,
+            log,
+            winners: HashMap::new(),
+            cipher,
+        };
+        replica.index();
+        Ok(replica)
+    }
+
+    /// Finds the operations in effect and rebuilds the base state from them
+    fn index(&mut self) {
+        self.winners.clear();
+        for (i, op) in self.log.ops.iter().enumerate() {
+            let key = op.change.key(&self.log.aliases);
+            if self
+                .winners
+                .get(&key)
+                .is_none_or(|&current| wins(op, &self.log.ops[current]))
+            {
+                self.winners.insert(key, i);
+            }
+        }
+        let mut winners = self.winners.values().copied().collect::<Vec<_>>();
+        // Days first, as the rest of their changes need them
+        winners.sort_by_key(|&i| {
+            let op = &self.log.ops[i];
+            (
+                !matches!(op.change, Change::Day { .. }),
+                op.clock.total(),
+                i,
+            )
+        });
+        self.log.base = State::default();
+        for i in winners {
+            self.log.ops[i]
+                .change
+                .apply(&mut self.log.base, &self.log.aliases);
+        }
+    }
+
+    /// Merges the days added for the same date into the one with the lowest id,
+    /// returning whether there were any
+    fn reconcile(&mut self, calendar: &Calendar) -> bool {
+        let mut by_date = HashMap::<_, Vec<Uuid>>::new();
+        for day in self.log.base.days.values() {
+            by_date
+                .entry(calendar.date_of(day.date))
+                .or_default()
+                .push(day.id);
+        }
+        let mut merged = false;
+        for ids in by_date.values().filter(|ids| ids.len() > 1) {
+            let into = *ids.iter().min().expect("there are several days");
+            for &id in ids.iter().filter(|&&id| id != into) {
+                info!("Merging day {} into {} of the same date", id, into);
+                self.log.aliases.insert(id, into);
+                merged = true;
+            }
+        }
+        if merged {
+            self.index();
+        }
+        merged
+    }
+
+    /// Drops the operations that others superseded, once the server has all of
+    /// them
+    fn compact(&mut self) {
+        let before = self.log.ops.len();
+        let mut winners = self.winners.values().copied().collect::<Vec<_>>();
+        winners.sort_unstable();
+        let ops = std::mem::take(&mut self.log.ops);
+        self.log.ops = ops
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| winners.binary_search(i).is_ok())
+            .map(|(_, op)| op)
+            .collect();
+        self.winners = self
+            .log
+            .ops
+            .iter()
+            .enumerate()
+            .map(|(i, op)| (op.change.key(&self.log.aliases), i))
+            .collect();
+        trace!(
+            "Compacted sync log from {} to {} operations",
+            before,
+            self.log.ops.len()
+        );
+    }
+
+    pub fn save(&self) -> Result<()> {
+        trace!("Saving sync log to {:?}", self.path);
+        let contents = crypto::encode(serde_json::to_string(&self.log)?, self.cipher.as_ref())?;
+        storage::write(&self.path, &contents)
+    }
+
+    /// Encrypts the log with another key from the next save on
+    pub fn set_cipher(&mut self, cipher: Option<Cipher>) {
+        self.cipher = cipher;
+    }
+
+    pub fn clock(&self) -> &VectorClock {
+        &self.log.clock
+    }
+
+    pub fn ops(&self) -> &[Op<Change>] {
+        &self.log.ops
+    }
+
+    /// Turns the local edits made since the last call into operations
+    pub fn record(&mut self, state: &State) {
+        let changes = diff(&self.log.base, state);
+        if changes.is_empty() {
+            return;
+        }
+        trace!("Recording {} local changes", changes.len());
+        let time = chrono::Utc::now();
+        for change in changes {
+            let counter = self.log.clock.increment(self.log.replica);
+            self.winners
+                .insert(change.key(&self.log.aliases), self.log.ops.len());
+            self.log.ops.push(Op {
+                replica: self.log.replica,
+                counter,
+                clock: self.log.clock.clone(),
+                time,
+                change,
+            });
+        }
+        self.log.base = state.clone();
+    }
+
+    /// Merges operations from other replicas into `state`, returning how many of
+    /// them changed it. `state` is expected to have been recorded.
+    pub fn receive(
+        &mut self,
+        mut ops: Vec<Op<Change>>,
+        state: &mut State,
+        calendar: &Calendar,
+    ) -> usize {
+        ops.sort_by_key(|op| op.clock.total());
+        let mut applied = 0;
+        for op in ops {
+            if self.log.clock.contains(&op) {
+                continue;
+            }
+            self.log.clock.observe(&op);
+            let key = op.change.key(&self.log.aliases);
+            if self
+                .winners
+                .get(&key)
+                .is_none_or(|&current| wins(&op, &self.log.ops[current]))
+            {
+                op.change.apply(state, &self.log.aliases);
+                op.change.apply(&mut self.log.base, &self.log.aliases);
+                self.winners.insert(key, self.log.ops.len());
+                applied += 1;
+            }
+            self.log.ops.push(op);
+        }
+        if self.reconcile(calendar) {
+            state.days = self.log.base.days.clone();
+        }
+        applied
+    }
+}
+
+fn send<T: Serialize>(stream: &mut TcpStream, message: &T) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+fn receive<T: serde::de::DeserializeOwned>(reader: &mut impl BufRead) -> Result<T> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(eyre::eyre!("Sync server closed the connection"));
+    }
+    Ok(serde_json::from_str(&line)?)
+}
+
+fn seal(op: Op<Change>, keys: Option<&mut SyncKeys>) -> Result<Op<Payload>> {
+    Ok(Op {
+        replica: op.replica,
+        counter: op.counter,
+        clock: op.clock,
+        time: op.time,
+        change: Payload::seal(op.change, keys)?,
+    })
+}
+
+fn open(op: Op<Payload>, keys: Option<&mut SyncKeys>) -> Result<Op<Change>> {
+    Ok(Op {
+        replica: op.replica,
+        counter: op.counter,
+        clock: op.clock,
+        time: op.time,
+        change: op.change.open(keys)?,
+    })
+}
+
+/// Trades operations with the sync server at `server`, given everything this
+/// replica has, and returns the operations it was missing. Changes are sealed
+/// with `keys` on the way out and opened with them on the way in.
+pub fn exchange(
+    server: &str,
+    clock: VectorClock,
+    ops: Vec<Op<Change>>,
+    mut keys: Option<SyncKeys>,
+) -> Result<Vec<Op<Change>>> {
+    info!("Syncing with {}", server);
+    let mut stream = TcpStream::connect(server)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    send(&mut stream, &ClientMessage::<Payload>::Hello { clock })?;
+    let ServerMessage::Missing {
+        clock: server_clock,
+        ops: received,
+    } = receive(&mut reader)?
+    else {
+        return Err(eyre::eyre!("Unexpected reply from sync server"));
+    };
+    let received = received
+        .into_iter()
+        .map(|op| open(op, keys.as_mut()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let push = ops
+        .into_iter()
+        .filter(|op| !server_clock.contains(op))
+        .map(|op| seal(op, keys.as_mut()))
+        .collect::<Result<Vec<_>>>()?;
+    info!(
+        "Received {} operations, sending {}",
+        received.len(),
+        push.len()
+    );
+    send(&mut stream, &ClientMessage::Push { ops: push })?;
+    match receive(&mut reader)? {
+        ServerMessage::<Payload>::Done => Ok(received),
+        _ => Err(eyre::eyre!("Unexpected reply from sync server")),
+    }
+}
+
+/// Records local edits, trades operations with `server` and merges the ones
+/// received into `state`. Blocks, so in async code run it with `spawn_blocking`.
+pub fn sync(
+    replica: &mut Replica,
+    server: &str,
+    state: &mut State,
+    calendar: &Calendar,
+) -> Result<usize> {
+    replica.record(state);
+    let received = exchange(
+        server,
+        replica.clock().clone(),
+        replica.ops().to_vec(),
+        replica
+            .cipher
+            .as_ref()
+            .map(|cipher| cipher.sync_keys(replica.log.salt)),
+    )?;
+    let applied = replica.receive(received, state, calendar);
+    // The server now has every operation this replica knows of
+    replica.compact();
+    replica.save()?;
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{NonEmpty, Validate};
+
+    const CALENDAR: Calendar = Calendar {
+        timezone: None,
+        rollover_hour: 0,
+    };
+
+    fn replica() -> Replica {
+        Replica::open(Path::new("/nonexistent/db.json"), None).unwrap()
+    }
+
+    /// Hands each replica the operations of the other, like a round of syncs
+    fn trade(a: &mut Replica, a_state: &mut State, b: &mut Replica, b_state: &mut State) {
+        a.record(a_state);
+        b.record(b_state);
+        let (a_ops, b_ops) = (a.ops().to_vec(), b.ops().to_vec());
+        a.receive(b_ops, a_state, &CALENDAR);
+        b.receive(a_ops, b_state, &CALENDAR);
+    }
+
+    #[test]
+    fn concurrent_edits_converge() {
+        let (mut a, mut b) = (replica(), replica());
+        let (mut a_state, mut b_state) = (State::default(), State::default());
+
+        a_state.add_day(chrono::Utc::now(), &CALENDAR).unwrap();
+        let day_id = *a_state.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Run".to_string()).unwrap();
+        a_state.add_habit_to_day(title, day_id).unwrap();
+        let habit_id = *a_state.habits.keys().next().unwrap();
+        trade(&mut a, &mut a_state, &mut b, &mut b_state);
+        assert!(!differs(&a_state, &b_state));
+
+        // Both check off the same habit, then b unchecks it after seeing a's edit
+        a_state
+            .set_habit_status(day_id, habit_id, HabitStatus::Done)
+            .unwrap();
+        b_state
+            .set_habit_status(day_id, habit_id, HabitStatus::Done)
+            .unwrap();
+        trade(&mut a, &mut a_state, &mut b, &mut b_state);
+        b_state
+            .set_habit_status(day_id, habit_id, HabitStatus::Pending)
+            .unwrap();
+        trade(&mut a, &mut a_state, &mut b, &mut b_state);
+
+        assert!(!differs(&a_state, &b_state));
+        assert_eq!(
+            a_state.days[&day_id].habits[&habit_id].status,
+            HabitStatus::Pending
+        );
+    }
+
+    #[test]
+    fn renames_keep_concurrent_check_ins() {
+        let (mut a, mut b) = (replica(), replica());
+        let (mut a_state, mut b_state) = (State::default(), State::default());
+        a_state.add_day(chrono::Utc::now(), &CALENDAR).unwrap();
+        let day_id = *a_state.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Run".to_string()).unwrap();
+        a_state.add_habit_to_day(title, day_id).unwrap();
+        let habit_id = *a_state.habits.keys().next().unwrap();
+        trade(&mut a, &mut a_state, &mut b, &mut b_state);
+
+        // a renames after b checks in, the rename alone must not undo the check-in
+        b_state
+            .set_habit_status(day_id, habit_id, HabitStatus::Done)
+            .unwrap();
+        b.record(&b_state);
+        let before = a_state.clone();
+        let title = NonEmpty::new_validated("Jog".to_string()).unwrap();
+        a_state.rename_habit(habit_id, title).unwrap();
+        assert!(matches!(diff(&before, &a_state)[..], [Change::Habit(_)]));
+        trade(&mut a, &mut a_state, &mut b, &mut b_state);
+
+        assert!(!differs(&a_state, &b_state));
+        assert_eq!(a_state.habits[&habit_id].title, "Jog");
+        assert_eq!(
+            a_state.days[&day_id].habits[&habit_id].status,
+            HabitStatus::Done
+        );
+    }
+
+    #[test]
+    fn days_of_the_same_date_merge() {
+        let (mut a, mut b) = (replica(), replica());
+        let (mut a_state, mut b_state) = (State::default(), State::default());
+        let now = chrono::Utc::now();
+        a_state
+            .add_day(now - chrono::TimeDelta::days(1), &CALENDAR)
+            .unwrap();
+        let yesterday = *a_state.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Run".to_string()).unwrap();
+        a_state.add_habit_to_day(title, yesterday).unwrap();
+        let run = *a_state.habits.keys().next().unwrap();
+        trade(&mut a, &mut a_state, &mut b, &mut b_state);
+
+        // Each adds today before hearing of the other's
+        a_state.add_day(now, &CALENDAR).unwrap();
+        b_state.add_day(now, &CALENDAR).unwrap();
+        let a_today = a_state.day_on(CALENDAR.today(), &CALENDAR).unwrap().id;
+        let b_today = b_state.day_on(CALENDAR.today(), &CALENDAR).unwrap().id;
+        a_state
+            .set_habit_status(a_today, run, HabitStatus::Done)
+            .unwrap();
+        b_state
+            .set_day_journal(b_today, "Rainy".to_string())
+            .unwrap();
+        trade(&mut a, &mut a_state, &mut b, &mut b_state);
+
+        assert!(!differs(&a_state, &b_state));
+        assert_eq!(a_state.days.len(), 2);
+        let today = a_state.day_on(CALENDAR.today(), &CALENDAR).unwrap();
+        assert_eq!(today.id, a_today.min(b_today));
+        assert_eq!(today.habits[&run].status, HabitStatus::Done);
+        assert_eq!(today.journal.as_deref(), Some("Rainy"));
+
+        // Later changes land on the merged day on both sides
+        let today = today.id;
+        b_state.set_day_mood(today, Some(5)).unwrap();
+        trade(&mut a, &mut a_state, &mut b, &mut b_state);
+        assert!(!differs(&a_state, &b_state));
+        assert_eq!(a_state.days[&today].mood, Some(5));
+        let mut reopened = replica();
+        let mut state = State::default();
+        reopened.receive(a.ops().to_vec(), &mut state, &CALENDAR);
+        assert!(!differs(&state, &a_state));
+    }
+
+    #[test]
+    fn pauses_sync() {
+        let (mut a, mut b) = (replica(), replica());
+        let (mut a_state, mut b_state) = (State::default(), State::default());
+        let today = CALENDAR.today();
+        let pause = crate::types::Pause::new(today, today + chrono::TimeDelta::days(3), None);
+        let pause_id = pause.id;
+        a_state.add_pause(pause).unwrap();
+        a.record(&a_state);
+        assert!(a
+            .ops()
+            .iter()
+            .any(|op| op.change.key(&HashMap::new()) == Key::Pause(pause_id)));
+        trade(&mut a, &mut a_state, &mut b, &mut b_state);
+        assert_eq!(b_state.pauses[&pause_id].end, a_state.pauses[&pause_id].end);
+
+        // Ending it on b moves its end on a too, rather than bringing it back
+        b_state.end_pause(pause_id, &CALENDAR).unwrap();
+        trade(&mut a, &mut a_state, &mut b, &mut b_state);
+        assert!(!differs(&a_state, &b_state));
+        assert!(!a_state.is_paused(Uuid::new_v4(), today));
+    }
+
+    #[test]
+    fn compacts_to_ops_in_effect() {
+        let mut a = replica();
+        let mut state = State::default();
+        state.add_day(chrono::Utc::now(), &CALENDAR).unwrap();
+        let day_id = *state.days.keys().next().unwrap();
+        let title = NonEmpty::new_validated("Run".to_string()).unwrap();
+        state.add_habit_to_day(title, day_id).unwrap();
+        let habit_id = *state.habits.keys().next().unwrap();
+        a.record(&state);
+        for status in [HabitStatus::Done, HabitStatus::Pending, HabitStatus::Done] {
+            state.set_habit_status(day_id, habit_id, status).unwrap();
+            a.record(&state);
+        }
+        assert_eq!(a.ops().len(), 6);
+
+        a.compact();
+        // The day, the habit and its check-in
+        assert_eq!(a.ops().len(), 3);
+
+        // Reopening rebuilds the base from the log, so nothing is recorded twice
+        let log = serde_json::to_string(&a.log).unwrap();
+        let mut reopened = Replica {
+            path: a.path.clone(),
+            log: serde_json::from_str(&log).unwrap(),
+            winners: HashMap::new(),
+            cipher: None,
+        };
+        reopened.index();
+        assert!(!differs(&reopened.log.base, &state));
+        reopened.record(&state);
+        assert_eq!(reopened.ops().len(), 3);
+    }
+
+    #[test]
+    fn seals_payloads() {
+        let passphrase = crypto::Passphrase::new("correct horse".to_string());
+        let cipher = Cipher::new(&passphrase).unwrap();
+        let change = Change::Journal {
+            day_id: Uuid::new_v4(),
+            journal: Some("Secret".to_string()),
+            mood: Some(3),
+        };
+        let mut keys = cipher.sync_keys(crypto::random_salt());
+        let sealed =
+            serde_json::to_string(&Payload::seal(change.clone(), Some(&mut keys)).unwrap())
+                .unwrap();
+        assert!(!sealed.contains("Secret"));
+
+        let payload = serde_json::from_str::<Payload>(&sealed).unwrap();
+        assert!(payload.open(None).is_err());
+        let payload = serde_json::from_str::<Payload>(&sealed).unwrap();
+        assert_eq!(payload.open(Some(&mut keys)).unwrap(), change);
+        // A server could make up plain changes, so an encrypted database refuses them
+        let plain = serde_json::to_string(&Payload::seal(change.clone(), None).unwrap()).unwrap();
+        let payload = serde_json::from_str::<Payload>(&plain).unwrap();
+        assert!(payload.open(Some(&mut keys)).is_err());
+        let payload = serde_json::from_str::<Payload>(&plain).unwrap();
+        assert_eq!(payload.open(None).unwrap(), change);
+    }
+}
@@ -0,0 +1,90 @@
+//! Wire format shared by the app and `ritual-sync-server`.
+//!
+//! Messages are single lines of JSON over TCP. A sync is one exchange: the client
+//! says which operations it has seen, the server answers with the ones the client
+//! is missing and what the server has seen, and the client pushes the rest.
+//!
+//! This file is also compiled into the server binary, so it must not depend on the
+//! rest of the crate.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// How many operations of each replica have been seen
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorClock(BTreeMap<Uuid, u64>);
+
+impl VectorClock {
+    pub fn get(&self, replica: Uuid) -> u64 {
+        self.0.get(&replica).copied().unwrap_or(0)
+    }
+
+    /// Counts a new operation of `replica`, returning its counter
+    pub fn increment(&mut self, replica: Uuid) -> u64 {
+        let counter = self.0.entry(replica).or_default();
+        *counter += 1;
+        *counter
+    }
+
+    /// Records that `op` has been seen
+    pub fn observe<C>(&mut self, op: &Op<C>) {
+        let counter = self.0.entry(op.replica).or_default();
+        *counter = (*counter).max(op.counter);
+    }
+
+    pub fn contains<C>(&self, op: &Op<C>) -> bool {
+        self.get(op.replica) >= op.counter
+    }
+
+    /// Whether everything seen by `self` had also been seen by `other`, which saw more
+    pub fn happened_before(&self, other: &VectorClock) -> bool {
+        self != other && self.0.iter().all(|(replica, n)| other.get(*replica) >= *n)
+    }
+
+    /// Total number of operations seen, which orders operations consistently with
+    /// causality
+    pub fn total(&self) -> u64 {
+        self.0.values().sum()
+    }
+}
+
+/// A change made by one replica
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Op<C> {
+    pub replica: Uuid,
+    /// Position among the operations of its replica, starting at 1
+    pub counter: u64,
+    /// Operations the replica had seen when it made this one, including itself
+    pub clock: VectorClock,
+    /// Wall clock time, only used to settle concurrent operations
+    pub time: DateTime<Utc>,
+    pub change: C,
+}
+
+impl<C> Op<C> {
+    /// Last writer wins: an operation replaces the ones it has seen, and concurrent
+    /// ones are settled by time and then by replica id so every replica agrees
+    pub fn supersedes<D>(&self, other: &Op<D>) -> bool {
+        if other.clock.happened_before(&self.clock) {
+            true
+        } else if self.clock.happened_before(&other.clock) {
+            false
+        } else {
+            (self.time, self.replica) > (other.time, other.replica)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ClientMessage<C> {
+    Hello { clock: VectorClock },
+    Push { ops: Vec<Op<C>> },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ServerMessage<C> {
+    Missing { clock: VectorClock, ops: Vec<Op<C>> },
+    Done,
+}
@@ -0,0 +1,21 @@
+//! Fixtures shared by the tests of several modules
+
+use chrono::NaiveDate;
+
+use crate::types::HabitStatus;
+
+/// Check-ins from 2025-03-01 on, one day per letter: `d`one, `s`kipped, `f`ailed or
+/// `p`ending
+pub fn parse_check_ins(statuses: &str) -> Vec<(NaiveDate, HabitStatus)> {
+    let start = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    let status = |c| match c {
+        'd' => HabitStatus::Done,
+        's' => HabitStatus::Skipped,
+        'f' => HabitStatus::Failed,
+        _ => HabitStatus::Pending,
+    };
+    start
+        .iter_days()
+        .zip(statuses.chars().map(status))
+        .collect()
+}
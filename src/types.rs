@@ -1,6 +1,7 @@
+use chrono::{NaiveDate, NaiveTime, Weekday};
 use indexmap::IndexMap;
-use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt::Display;
 use uuid::Uuid;
 
 pub type DateTime = chrono::DateTime<chrono::Utc>;
@@ -10,14 +11,24 @@ pub struct Day {
     pub id: Uuid,
     pub date: DateTime,
     pub habits: IndexMap<Uuid, HabitRef>,
+    /// Journal entry in markdown
+    #[serde(default)]
+    pub journal: Option<String>,
+    /// How the day felt, from 1 to 5
+    #[serde(default)]
+    pub mood: Option<u8>,
 }
 
 impl Day {
+    pub const MOODS: std::ops::RangeInclusive<u8> = 1..=5;
+
     pub fn new(date: DateTime) -> Self {
         Self {
             id: Uuid::new_v4(),
             date,
             habits: Default::default(),
+            journal: None,
+            mood: None,
         }
     }
 }
@@ -26,13 +37,150 @@ impl Day {
 pub struct Habit {
     pub id: Uuid,
     pub title: String,
+    #[serde(default)]
+    pub schedule: Schedule,
+    /// Local time of day at which to remind about the habit if it is not done yet
+    #[serde(default)]
+    pub reminder: Option<NaiveTime>,
+    /// Accent color in `#rrggbb` form, the theme's primary color is used when unset
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Archived habits are no longer added to new days but keep their history
+    #[serde(default)]
+    pub archived: bool,
+}
+
+/// Days of the week on which a habit is instantiated
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Schedule {
+    pub weekdays: Vec<Weekday>,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            weekdays: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+            ],
+        }
+    }
+}
+
+impl Schedule {
+    pub fn includes(&self, date: NaiveDate) -> bool {
+        use chrono::Datelike;
+        self.weekdays.contains(&date.weekday())
+    }
+
+    pub fn toggle(&mut self, weekday: Weekday) {
+        if let Some(pos) = self.weekdays.iter().position(|d| *d == weekday) {
+            self.weekdays.remove(pos);
+        } else {
+            self.weekdays.push(weekday);
+            self.weekdays.sort_by_key(|d| d.num_days_from_monday());
+        }
+    }
+}
+
+/// Days on which habits are not expected to be done, such as while travelling or
+/// ill. Paused habits are left out of new days, streaks and stats.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Pause {
+    pub id: Uuid,
+    pub start: NaiveDate,
+    /// Last paused day
+    pub end: NaiveDate,
+    /// The paused habit, every habit is paused when unset
+    #[serde(default)]
+    pub habit_id: Option<Uuid>,
+}
+
+impl Pause {
+    pub fn new(start: NaiveDate, end: NaiveDate, habit_id: Option<Uuid>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            start,
+            end,
+            habit_id,
+        }
+    }
+
+    pub fn is_on(&self, date: NaiveDate) -> bool {
+        (self.start..=self.end).contains(&date)
+    }
+
+    pub fn covers(&self, habit_id: Uuid, date: NaiveDate) -> bool {
+        self.is_on(date) && self.habit_id.is_none_or(|paused| paused == habit_id)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq)]
 pub struct HabitRef {
     pub id: Uuid,
     pub name: String,
-    pub done: bool,
+    #[serde(alias = "done", deserialize_with = "HabitStatus::deserialize_compat")]
+    pub status: HabitStatus,
+    /// A few words about this check-in
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// How a habit went on a day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HabitStatus {
+    #[default]
+    Pending,
+    Done,
+    /// Deliberately not done, such as when sick or travelling. Neither counts for
+    /// nor against the habit.
+    Skipped,
+    Failed,
+}
+
+impl HabitStatus {
+    /// The status after this one when cycling through them
+    pub fn next(self) -> Self {
+        match self {
+            HabitStatus::Pending => HabitStatus::Done,
+            HabitStatus::Done => HabitStatus::Skipped,
+            HabitStatus::Skipped => HabitStatus::Failed,
+            HabitStatus::Failed => HabitStatus::Pending,
+        }
+    }
+
+    /// Databases from before there were statuses store `done: bool`, not being
+    /// done meant nothing more than pending
+    fn deserialize_compat<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Compat {
+            Done(bool),
+            Status(HabitStatus),
+        }
+        Ok(match Compat::deserialize(deserializer)? {
+            Compat::Done(true) => HabitStatus::Done,
+            Compat::Done(false) => HabitStatus::Pending,
+            Compat::Status(status) => status,
+        })
+    }
+}
+
+impl Display for HabitStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HabitStatus::Pending => write!(f, "pending"),
+            HabitStatus::Done => write!(f, "done"),
+            HabitStatus::Skipped => write!(f, "skipped"),
+            HabitStatus::Failed => write!(f, "failed"),
+        }
+    }
 }
 
 impl PartialEq for HabitRef {
@@ -58,3 +206,28 @@ impl PartialEq for Habit {
         self.id == other.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn habit_ref_migrates_done() {
+        let id = Uuid::new_v4();
+        let parse = |json: String| serde_json::from_str::<HabitRef>(&json).unwrap().status;
+        assert_eq!(
+            parse(format!(r#"{{"id":"{id}","name":"Run","done":true}}"#)),
+            HabitStatus::Done
+        );
+        assert_eq!(
+            parse(format!(r#"{{"id":"{id}","name":"Run","done":false}}"#)),
+            HabitStatus::Pending
+        );
+        assert_eq!(
+            parse(format!(
+                r#"{{"id":"{id}","name":"Run","status":"skipped"}}"#
+            )),
+            HabitStatus::Skipped
+        );
+    }
+}
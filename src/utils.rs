@@ -84,7 +84,7 @@ where
 impl Validate for NonEmpty<String> {
     type Target = String;
     fn new_validated(t: Self::Target) -> Result<Self, ValidationError> {
-        if t == "" {
+        if t.is_empty() {
             Err(ValidationError::InvalidValue)
         } else {
             Ok(NonEmpty(t))
@@ -95,6 +95,7 @@ impl Validate for NonEmpty<String> {
     }
 }
 
+#[allow(dead_code)]
 pub struct NonZero<T>(T);
 
 impl Validate for NonZero<u32> {
@@ -110,3 +111,49 @@ impl Validate for NonZero<u32> {
         self.0
     }
 }
+
+/// A CSS color in `#rrggbb` form
+pub struct HexColor(String);
+
+impl Validate for HexColor {
+    type Target = String;
+    fn new_validated(t: Self::Target) -> Result<Self, ValidationError> {
+        let valid =
+            t.len() == 7 && t.starts_with('#') && t.chars().skip(1).all(|c| c.is_ascii_hexdigit());
+        if valid {
+            Ok(HexColor(t.to_lowercase()))
+        } else {
+            Err(ValidationError::InvalidValue)
+        }
+    }
+    fn inner(self) -> String {
+        self.0
+    }
+}
+
+/// A file next to `path` with `extension` appended to its name, e.g. `db.json.lock`
+#[cfg(not(feature = "web"))]
+pub fn sibling_path(path: &std::path::Path, extension: &str) -> std::path::PathBuf {
+    let mut sibling = path.as_os_str().to_owned();
+    sibling.push(".");
+    sibling.push(extension);
+    sibling.into()
+}
+
+/// Whether two values differ in any field. `Habit` and `HabitRef` compare by id
+/// only, so this is how to tell if one was edited.
+#[cfg(not(feature = "web"))]
+pub fn differs<T: serde::Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() != serde_json::to_value(b).ok()
+}
+
+/// Sleeps on whichever timer the platform supports, tokio's timer does not work in the browser
+#[cfg(not(feature = "web"))]
+pub async fn sleep(duration: std::time::Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(feature = "web")]
+pub async fn sleep(duration: std::time::Duration) {
+    gloo_timers::future::sleep(duration).await
+}
@@ -0,0 +1,56 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+use crate::prelude::*;
+
+/// Watches a single file for changes.
+///
+/// The parent directory is watched rather than the file itself, so that the file
+/// being created, deleted or atomically replaced by an editor is noticed too.
+pub struct FileWatcher {
+    _watcher: RecommendedWatcher,
+    rx: mpsc::UnboundedReceiver<()>,
+}
+
+impl FileWatcher {
+    #[tracing::instrument]
+    pub fn new(path: &Path) -> Result<Self> {
+        let path: PathBuf = path.into();
+        let dir = path
+            .parent()
+            .ok_or_else(|| color_eyre::eyre::eyre!("{:?} has no parent directory", path))?
+            .to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) if event.kind.is_access() => {}
+                Ok(event)
+                    if event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name() == path.file_name()) =>
+                {
+                    let _ = tx.send(());
+                }
+                Ok(_) => {}
+                Err(err) => error!("File watcher error: {}", err),
+            })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+        })
+    }
+
+    /// Waits for the next change, coalescing bursts of events into one
+    pub async fn changed(&mut self) -> Option<()> {
+        self.rx.recv().await?;
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        while self.rx.try_recv().is_ok() {}
+        Some(())
+    }
+}
@@ -0,0 +1,78 @@
+use dioxus::desktop::{DesktopContext, LogicalPosition, LogicalSize, WindowBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::config;
+use crate::prelude::*;
+
+/// Size and position of the main window, restored on the next launch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub width: f64,
+    pub height: f64,
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self {
+            width: 1000.0,
+            height: 720.0,
+            x: None,
+            y: None,
+            maximized: false,
+        }
+    }
+}
+
+fn path() -> PathBuf {
+    config::data_dir().join("window.json")
+}
+
+impl WindowState {
+    pub fn load() -> Self {
+        std::fs::read_to_string(path())
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = path();
+        trace!("Saving window state to {:?}", path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn of_window(window: &DesktopContext) -> Self {
+        let scale = window.scale_factor();
+        let size = window.inner_size().to_logical::<f64>(scale);
+        let position = window
+            .outer_position()
+            .ok()
+            .map(|p| p.to_logical::<f64>(scale));
+        Self {
+            width: size.width,
+            height: size.height,
+            x: position.map(|p| p.x),
+            y: position.map(|p| p.y),
+            maximized: window.is_maximized(),
+        }
+    }
+
+    pub fn apply(&self, builder: WindowBuilder) -> WindowBuilder {
+        let builder = builder
+            .with_inner_size(LogicalSize::new(self.width, self.height))
+            .with_maximized(self.maximized);
+        match (self.x, self.y) {
+            (Some(x), Some(y)) => builder.with_position(LogicalPosition::new(x, y)),
+            _ => builder,
+        }
+    }
+}